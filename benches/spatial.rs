@@ -0,0 +1,89 @@
+//! Compares scanning every enemy against `SpatialGrid` queries, the way bullet
+//! collision and tower targeting look for nearby enemies each frame.
+//!
+//! Run with `cargo bench --bench spatial`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+#[allow(dead_code)]
+#[path = "../src/spatial.rs"]
+mod spatial;
+
+use spatial::SpatialGrid;
+
+const ENEMY_COUNTS: [usize; 4] = [250, 1000, 4000, 10000];
+/// Roughly one query per bullet in flight.
+const QUERIES: usize = 1000;
+const RADIUS: f32 = 34.0;
+const FRAMES: u32 = 20;
+const WORLD_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+fn random_point(rng: &mut impl Rng) -> Vec2 {
+    return Vec2::new(
+        rng.gen_range(-WORLD_SIZE.x / 2.0..WORLD_SIZE.x / 2.0),
+        rng.gen_range(-WORLD_SIZE.y / 2.0..WORLD_SIZE.y / 2.0),
+    );
+}
+
+fn linear(enemies: &[(Entity, Vec2)], points: &[Vec2]) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut found = 0;
+
+    for _ in 0..FRAMES {
+        for point in points {
+            found += enemies.iter()
+                .filter(|(_, position)| position.distance_squared(*point) <= RADIUS * RADIUS)
+                .count();
+        }
+    }
+
+    return (start.elapsed() / FRAMES, black_box(found));
+}
+
+/// Includes rebuilding the grid, as the game does once per frame.
+fn grid(enemies: &[(Entity, Vec2)], points: &[Vec2]) -> (Duration, usize) {
+    let mut grid = SpatialGrid::default();
+    let start = Instant::now();
+    let mut found = 0;
+
+    for _ in 0..FRAMES {
+        grid.clear();
+        for (entity, position) in enemies {
+            grid.insert(*entity, *position);
+        }
+
+        for point in points {
+            found += grid.query_radius(*point, RADIUS).len();
+        }
+    }
+
+    return (start.elapsed() / FRAMES, black_box(found));
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let points: Vec<Vec2> = (0..QUERIES).map(|_| random_point(&mut rng)).collect();
+
+    for count in ENEMY_COUNTS {
+        let enemies: Vec<(Entity, Vec2)> = (0..count)
+            .map(|i| (Entity::from_raw(i as u32), random_point(&mut rng)))
+            .collect();
+
+        let (linear_time, linear_found) = linear(&enemies, &points);
+        let (grid_time, grid_found) = grid(&enemies, &points);
+        assert_eq!(linear_found, grid_found, "grid and linear scan disagree");
+
+        println!(
+            "{:>6} enemies, {} queries per frame: linear {:>10.3?}, grid {:>10.3?} ({:.1}x)",
+            count,
+            QUERIES,
+            linear_time,
+            grid_time,
+            linear_time.as_secs_f64() / grid_time.as_secs_f64(),
+        );
+    }
+}
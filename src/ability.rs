@@ -0,0 +1,202 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::enemy::{Enemy, Slow};
+use crate::frost;
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::support::TowerBuffs;
+use crate::tower::Tower;
+
+const ABILITY_KEY: KeyCode = KeyCode::Q;
+const OVERLAY_RADIUS: f32 = 20.0;
+/// Lines drawn across a full turn of the cooldown overlay, so it reads as filled.
+const OVERLAY_SPOKES: f32 = 64.0;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.55);
+const ACTIVE_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+
+type FreezableQuery<'w, 's> = Query<'w, 's, (&'static Transform, &'static mut Slow), (With<Enemy>, Without<Tower>)>;
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum AbilityEffect {
+    /// Adds `bonus` to the tower's fire rate for `duration` seconds.
+    RapidFire { duration: f32, bonus: f32 },
+    /// The tower's next volley deals `multiplier` times its damage.
+    Overcharge { multiplier: f32 },
+    /// Freezes every enemy in the tower's range for `duration` seconds.
+    FreezeRing { duration: f32 },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AbilityDefinition {
+    pub name: String,
+    pub cooldown: f32,
+    pub effect: AbilityEffect,
+}
+
+impl AbilityDefinition {
+    pub fn describe(&self) -> String {
+        let effect = match self.effect {
+            AbilityEffect::RapidFire { duration, bonus } => format!("+{}% fire rate for {}s", (bonus * 100.0).round(), duration),
+            AbilityEffect::Overcharge { multiplier } => format!("Next volley deals x{} damage", multiplier),
+            AbilityEffect::FreezeRing { duration } => format!("Freezes enemies in range for {}s", duration),
+        };
+
+        return format!("{} ({}s cooldown)", effect, self.cooldown);
+    }
+}
+
+/// An ability the player triggers from the tower panel or with `ABILITY_KEY`
+/// while the tower is selected.
+#[derive(Component)]
+pub struct TowerAbility {
+    pub definition: AbilityDefinition,
+    cooldown: f32,
+    /// Seconds left on an effect that lasts a while, like rapid fire.
+    active: f32,
+}
+
+impl TowerAbility {
+    pub fn new(definition: AbilityDefinition) -> TowerAbility {
+        return TowerAbility {
+            definition,
+            cooldown: 0.0,
+            active: 0.0,
+        };
+    }
+
+    pub fn is_ready(&self) -> bool {
+        return self.cooldown <= 0.0;
+    }
+
+    pub fn get_cooldown(&self) -> f32 {
+        return self.cooldown;
+    }
+}
+
+/// Asks for a tower's ability to be used. Ignored while it's on cooldown.
+#[derive(Event)]
+pub struct UseAbility {
+    pub tower: Entity,
+}
+
+pub struct AbilityPlugin;
+impl Plugin for AbilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UseAbility>();
+        app.add_systems(Update, ability_hotkey);
+        app.add_systems(Update, use_abilities.after(ability_hotkey));
+        app.add_systems(Update, update_abilities);
+        app.add_systems(Update, draw_cooldowns);
+    }
+}
+
+fn ability_hotkey(
+    keyboard: Res<Input<KeyCode>>,
+    tower_query: Query<(Entity, &Tower), With<TowerAbility>>,
+    mut ability_events: EventWriter<UseAbility>,
+) {
+    if (!keyboard.just_pressed(ABILITY_KEY)) {
+        return;
+    }
+
+    for (entity, tower) in tower_query.iter() {
+        if (tower.is_selected()) {
+            ability_events.send(UseAbility { tower: entity });
+        }
+    }
+}
+
+fn use_abilities(
+    mut commands: Commands,
+    mut ability_events: EventReader<UseAbility>,
+    mut tower_query: Query<(&mut Tower, &mut TowerAbility, &Transform)>,
+    mut enemy_query: FreezableQuery,
+    grid: Res<SpatialGrid>,
+    images: Res<Images>,
+) {
+    for event in ability_events.read() {
+        let Ok((mut tower, mut ability, transform)) = tower_query.get_mut(event.tower) else {
+            continue;
+        };
+        if (!tower.activated || !ability.is_ready()) {
+            continue;
+        }
+
+        match ability.definition.effect {
+            AbilityEffect::RapidFire { duration, bonus } => {
+                ability.active = duration;
+                tower.set_ability_bonus(TowerBuffs { rate_of_fire: bonus, ..default() });
+            }
+            AbilityEffect::Overcharge { multiplier } => {
+                tower.overcharge(multiplier);
+            }
+            AbilityEffect::FreezeRing { duration } => {
+                for (entity, _) in grid.query_radius(transform.translation.truncate(), tower.get_range()) {
+                    let Ok((e_transform, mut slow)) = enemy_query.get_mut(entity) else {
+                        continue;
+                    };
+                    if (Vec3::distance(transform.translation, e_transform.translation) <= tower.get_range()) {
+                        slow.freeze(duration);
+                    }
+                }
+
+                frost::spawn_pulse(&mut commands, &images, transform.translation, tower.get_range());
+            }
+        }
+
+        ability.cooldown = ability.definition.cooldown;
+    }
+}
+
+fn update_abilities(
+    mut tower_query: Query<(&mut Tower, &mut TowerAbility)>,
+    time: Res<Time>,
+) {
+    for (mut tower, mut ability) in tower_query.iter_mut() {
+        ability.cooldown = f32::max(ability.cooldown - time.delta_seconds(), 0.0);
+
+        if (ability.active > 0.0) {
+            ability.active -= time.delta_seconds();
+            if (ability.active <= 0.0) {
+                tower.set_ability_bonus(TowerBuffs::default());
+            }
+        }
+    }
+}
+
+/// Covers the tower with a clockwise sweep that shrinks as the cooldown runs
+/// out, with a ring around it while a lasting effect is active.
+fn draw_cooldowns(
+    mut gizmos: Gizmos,
+    tower_query: Query<(&Tower, &TowerAbility, &Transform)>,
+) {
+    for (tower, ability, transform) in tower_query.iter() {
+        if (!tower.activated) {
+            continue;
+        }
+
+        let center = transform.translation.truncate();
+
+        if (ability.active > 0.0) {
+            if let AbilityEffect::RapidFire { duration, .. } = ability.definition.effect {
+                let sweep = TAU * ability.active / duration;
+                gizmos.arc_2d(center, sweep / 2.0, sweep, OVERLAY_RADIUS + 4.0, ACTIVE_COLOR);
+            }
+        }
+
+        if (ability.is_ready()) {
+            continue;
+        }
+
+        let sweep = TAU * ability.cooldown / ability.definition.cooldown;
+        let spokes = f32::ceil(OVERLAY_SPOKES * sweep / TAU) as i32;
+        for i in 0..=spokes {
+            let angle = sweep * i as f32 / spokes as f32;
+            gizmos.line_2d(center, center + Vec2::new(angle.sin(), angle.cos()) * OVERLAY_RADIUS, OVERLAY_COLOR);
+        }
+        gizmos.arc_2d(center, sweep / 2.0, sweep, OVERLAY_RADIUS, OVERLAY_COLOR);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+/// Inclusive range of frames in a texture atlas.
+#[derive(Clone, Copy)]
+pub struct AnimationClip {
+    first: usize,
+    last: usize,
+}
+
+impl AnimationClip {
+    pub const fn new(first: usize, last: usize) -> AnimationClip {
+        return AnimationClip {
+            first,
+            last
+        };
+    }
+
+    pub fn first(&self) -> usize {
+        return self.first;
+    }
+}
+
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clip: AnimationClip,
+    frame_time: f32,
+    time: f32,
+    looping: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(clip: AnimationClip, fps: f32, looping: bool) -> SpriteAnimation {
+        return SpriteAnimation {
+            clip,
+            frame_time: 1.0 / fps,
+            time: 0.0,
+            looping,
+        };
+    }
+
+    pub fn play(&mut self, clip: AnimationClip, looping: bool) {
+        self.clip = clip;
+        self.time = 0.0;
+        self.looping = looping;
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        self.time += delta;
+
+        let duration = self.frame_time * (self.clip.last - self.clip.first + 1usize) as f32;
+        if (self.looping && self.time >= duration) {
+            self.time -= duration;
+        }
+    }
+
+    pub fn frame(&self) -> usize {
+        let frames = self.clip.last - self.clip.first + 1usize;
+        let frame = (self.time / self.frame_time) as usize;
+
+        if (self.looping) {
+            return self.clip.first + frame % frames;
+        }
+
+        return self.clip.first + usize::min(frame, frames - 1usize);
+    }
+
+    pub fn finished(&self) -> bool {
+        let frames = self.clip.last - self.clip.first + 1usize;
+        return !self.looping && self.time >= self.frame_time * frames as f32;
+    }
+}
+
+pub struct AnimationPlugin;
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, animate_sprites);
+    }
+}
+
+fn animate_sprites(
+    mut query: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        animation.advance(time.delta_seconds());
+        sprite.index = animation.frame();
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use crate::enemy::Enemy;
+use crate::game::Health;
+use crate::spatial::SpatialGrid;
+use crate::tower::{EnemyQuery, Tower};
+use crate::veterancy::{self, DamageDealt};
+
+const BEAM_WIDTH: f32 = 4.0;
+
+/// Locks onto a single target and deals the tower's damage every second. The
+/// damage multiplier ramps up for as long as the same target is held.
+#[derive(Component)]
+pub struct BeamTower {
+    pub ramp_rate: f32,
+    pub max_ramp: f32,
+    target: Option<Entity>,
+    time_on_target: f32,
+    pending_damage: f32,
+    beam: Option<Entity>,
+    beam_end: Option<Vec3>,
+}
+
+impl BeamTower {
+    pub fn new(ramp_rate: f32, max_ramp: f32) -> BeamTower {
+        return BeamTower {
+            ramp_rate,
+            max_ramp,
+            target: None,
+            time_on_target: 0.0,
+            pending_damage: 0.0,
+            beam: None,
+            beam_end: None,
+        };
+    }
+
+    pub fn ramp(&self) -> f32 {
+        return f32::min(1.0 + self.ramp_rate * self.time_on_target, self.max_ramp);
+    }
+}
+
+/// The visible beam, drawn as a stretched sprite between a beam tower and its target.
+#[derive(Component)]
+struct Beam {
+    tower: Entity,
+}
+
+pub struct BeamPlugin;
+impl Plugin for BeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_beam_tower);
+        app.add_systems(Update, draw_beams.after(update_beam_tower));
+    }
+}
+
+fn update_beam_tower(
+    mut commands: Commands,
+    mut tower_query: Query<(Entity, &mut Tower, &mut BeamTower, &mut Transform)>,
+    mut enemy_queries: ParamSet<(EnemyQuery, Query<&mut Health, With<Enemy>>)>,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageDealt>,
+) {
+    for (entity, mut tower, mut beam_tower, mut transform) in tower_query.iter_mut() {
+        if (beam_tower.beam.is_none()) {
+            let beam = commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(0.5, 1.0, 0.9, 0.8),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Beam { tower: entity }
+            )).id();
+            beam_tower.beam = Some(beam);
+        }
+
+        beam_tower.beam_end = None;
+
+        if (!tower.activated) {
+            beam_tower.target = None;
+            continue;
+        }
+
+        let target = tower.update_target(transform.translation, &enemy_queries.p0(), &grid);
+        if (target != beam_tower.target) {
+            beam_tower.target = target;
+            beam_tower.time_on_target = 0.0;
+            beam_tower.pending_damage = 0.0;
+        }
+
+        let Some(target) = target else {
+            continue;
+        };
+
+        let target_pos = enemy_queries.p0().get(target).unwrap().1.translation;
+        tower.set_direction(Vec3::normalize(target_pos - transform.translation));
+        tower.rotate_towards(&mut transform, target_pos);
+
+        beam_tower.time_on_target += time.delta_seconds();
+        beam_tower.pending_damage += tower.get_damage() as f32 * beam_tower.ramp() * time.delta_seconds();
+        beam_tower.beam_end = Some(target_pos);
+
+        let damage = beam_tower.pending_damage.floor();
+        if (damage >= 1.0) {
+            beam_tower.pending_damage -= damage;
+
+            if let Ok(mut health) = enemy_queries.p1().get_mut(target) {
+                veterancy::deal_damage(&mut health, damage as i32, Some(entity), &mut damage_events);
+            }
+        }
+    }
+}
+
+fn draw_beams(
+    mut commands: Commands,
+    mut beam_query: Query<(Entity, &Beam, &mut Transform, &mut Sprite, &mut Visibility)>,
+    tower_query: Query<(&BeamTower, &Transform), Without<Beam>>,
+) {
+    for (entity, beam, mut transform, mut sprite, mut visibility) in beam_query.iter_mut() {
+        let Ok((beam_tower, t_transform)) = tower_query.get(beam.tower) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let Some(end) = beam_tower.beam_end else {
+            (*visibility) = Visibility::Hidden;
+            continue;
+        };
+
+        let start = t_transform.translation;
+        let difference = end - start;
+        let length = difference.truncate().length();
+
+        transform.translation = Vec3::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0, 2.5);
+        transform.rotation = Quat::from_rotation_z(f32::atan2(difference.y, difference.x));
+        sprite.custom_size = Some(Vec2::new(length, BEAM_WIDTH * beam_tower.ramp()));
+        (*visibility) = Visibility::Visible;
+    }
+}
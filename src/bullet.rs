@@ -1,5 +1,13 @@
 use bevy::prelude::*;
-use crate::game::GameTimer;
+use serde::Deserialize;
+use crate::enemy::{self, Enemy, Stealth};
+use crate::explosion;
+use crate::game::{GameTimer, Health};
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+
+/// How far a homing bullet looks for a new target once its own is gone.
+const HOMING_RANGE: f32 = 150.0;
 
 #[derive(Component)]
 pub struct Bullet {
@@ -7,6 +15,12 @@ pub struct Bullet {
     direction: Vec3,
     speed: f32,
     lifetime: f32,
+    hit: Vec<Entity>,
+    previous: Option<Vec3>,
+    /// Whether `damage` was rolled as a critical hit.
+    crit: bool,
+    /// The tower that fired the bullet, credited with its damage.
+    source: Option<Entity>,
 }
 
 impl Bullet {
@@ -16,13 +30,47 @@ impl Bullet {
             direction: dir,
             speed: spd,
             lifetime: life,
+            hit: Vec::new(),
+            previous: None,
+            crit: false,
+            source: None,
         }
     }
 
+    pub fn with_crit(mut self, crit: bool) -> Bullet {
+        self.crit = crit;
+        return self;
+    }
+
+    pub fn is_crit(&self) -> bool {
+        return self.crit;
+    }
+
+    pub fn with_source(mut self, tower: Entity) -> Bullet {
+        self.source = Some(tower);
+        return self;
+    }
+
+    pub fn get_source(&self) -> Option<Entity> {
+        return self.source;
+    }
+
     pub fn update(&mut self, transform: &mut Transform, delta: f32) {
+        self.previous = Some(transform.translation);
         transform.translation += self.direction * self.speed * delta;
     }
 
+    /// The path the bullet covered in its last move. Bullets that haven't moved
+    /// yet get a zero-length segment at their position.
+    pub fn segment(&self, transform: &Transform) -> (Vec2, Vec2) {
+        let end = transform.translation.truncate();
+        return (self.previous.map_or(end, |previous| previous.truncate()), end);
+    }
+
+    pub fn get_direction(&self) -> Vec3 {
+        return self.direction;
+    }
+
     pub fn get_lifetime(&self) -> f32 {
         return self.lifetime;
     }
@@ -30,25 +78,169 @@ impl Bullet {
     pub fn get_damage(&self) -> i32 {
         return self.damage;
     }
+
+    /// Points the bullet along `dir` and turns its sprite to match.
+    pub fn set_direction(&mut self, transform: &mut Transform, dir: Vec3) {
+        self.direction = dir;
+        transform.rotation = Quat::from_rotation_z(f32::atan2(dir.y, dir.x));
+    }
+
+    /// Enemies already hit are skipped, so piercing and ricocheting bullets
+    /// only damage each enemy once.
+    pub fn has_hit(&self, enemy: Entity) -> bool {
+        return self.hit.contains(&enemy);
+    }
+
+    /// Whether the bullet has already hit at least one enemy.
+    pub fn has_landed(&self) -> bool {
+        return !self.hit.is_empty();
+    }
+
+    pub fn record_hit(&mut self, enemy: Entity) {
+        self.hit.push(enemy);
+    }
+}
+
+/// Steers the bullet towards `target` by at most `turn_rate` radians a second.
+#[derive(Component, Deserialize, Clone, Copy)]
+pub struct Homing {
+    pub turn_rate: f32,
+    #[serde(skip)]
+    pub target: Option<Entity>,
+}
+
+/// Lets the bullet pass through `count` more enemies before it's used up.
+#[derive(Component, Deserialize, Clone, Copy)]
+pub struct Pierce {
+    pub count: i32,
+}
+
+/// After a hit the bullet turns towards the closest enemy within `radius` that
+/// it hasn't hit yet, up to `bounces` times.
+#[derive(Component, Deserialize, Clone, Copy)]
+pub struct Ricochet {
+    pub bounces: i32,
+    pub radius: f32,
+}
+
+/// Bullets that blow up on impact or when their lifetime runs out, dealing
+/// their damage to everything in `radius` instead of a single enemy.
+#[derive(Component, Clone, Copy)]
+pub struct Explosive {
+    pub radius: f32,
+    pub falloff: f32,
+}
+
+impl Explosive {
+    pub fn new(radius: f32, falloff: f32) -> Explosive {
+        return Explosive {
+            radius,
+            falloff
+        };
+    }
 }
 
 pub struct BulletPlugin;
 impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_bullets);
+        app.add_systems(Update, steer_homing_bullets);
+        app.add_systems(Update, update_bullets.after(steer_homing_bullets));
+    }
+}
+
+/// Called once a bullet has damaged an enemy. Piercing is used up first, then
+/// ricochets to the closest enemy `bounce_targets` finds within a radius of a
+/// point. Returns whether the bullet keeps flying.
+pub fn continue_after_hit(
+    bullet: &mut Bullet,
+    transform: &mut Transform,
+    timer: &mut GameTimer,
+    pierce: Option<&mut Pierce>,
+    ricochet: Option<&mut Ricochet>,
+    homing: Option<&mut Homing>,
+    bounce_targets: impl Fn(Vec2, f32) -> Vec<(Entity, Vec2)>,
+) -> bool {
+    if let Some(pierce) = pierce.filter(|pierce| pierce.count > 0) {
+        pierce.count -= 1;
+        return true;
+    }
+
+    let Some(ricochet) = ricochet.filter(|ricochet| ricochet.bounces > 0) else {
+        return false;
+    };
+
+    let position = transform.translation.truncate();
+    let next = bounce_targets(position, ricochet.radius).into_iter()
+        .filter(|(entity, _)| !bullet.has_hit(*entity))
+        .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)));
+
+    let Some((next, next_position)) = next else {
+        return false;
+    };
+
+    ricochet.bounces -= 1;
+    bullet.set_direction(transform, (next_position - position).normalize_or_zero().extend(0.0));
+    timer.reset();
+
+    if let Some(homing) = homing {
+        homing.target = Some(next);
+    }
+
+    return true;
+}
+
+type HomingTargetQuery<'w, 's> = Query<'w, 's, (&'static Transform, &'static Health, Option<&'static Stealth>), (With<Enemy>, Without<Bullet>)>;
+
+fn steer_homing_bullets(
+    mut bullet_query: Query<(&mut Bullet, &mut Homing, &mut Transform)>,
+    enemy_query: HomingTargetQuery,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+) {
+    for (mut bullet, mut homing, mut transform) in bullet_query.iter_mut() {
+        let position = transform.translation;
+
+        let can_home_on = |entity| !bullet.has_hit(entity) && enemy_query.get(entity).is_ok_and(|(_, health, stealth)| enemy::is_targetable(health, stealth));
+        if (!homing.target.is_some_and(can_home_on)) {
+            homing.target = grid.query_radius(position.truncate(), HOMING_RANGE).into_iter()
+                .filter(|(entity, _)| can_home_on(*entity))
+                .min_by(|(_, a), (_, b)| a.distance(position.truncate()).total_cmp(&b.distance(position.truncate())))
+                .map(|(entity, _)| entity);
+        }
+
+        let Some(target) = homing.target else {
+            continue;
+        };
+
+        let wanted = (enemy_query.get(target).unwrap().0.translation - position).truncate();
+        if (wanted == Vec2::ZERO) {
+            continue;
+        }
+
+        let current = bullet.direction.truncate();
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let turn = f32::clamp(current.angle_between(wanted), -max_turn, max_turn);
+
+        let dir = Vec2::from_angle(turn).rotate(current).extend(0.0);
+        bullet.set_direction(&mut transform, dir);
     }
 }
 
 pub fn update_bullets(
     mut commands: Commands,
-    mut bullet_query: Query<(&mut Bullet, &mut Transform, &mut GameTimer, Entity)>,
-    time: Res<Time>
+    mut bullet_query: Query<(&mut Bullet, &mut Transform, &mut GameTimer, Entity, Option<&Explosive>)>,
+    time: Res<Time>,
+    images: Res<Images>,
 ) {
-    for (mut bullet, mut transform, mut timer, entity) in bullet_query.iter_mut() {
+    for (mut bullet, mut transform, mut timer, entity, explosive) in bullet_query.iter_mut() {
         bullet.update(&mut transform, time.delta_seconds());
         timer.add_time(time.delta_seconds());
 
         if (timer.get_time() >= bullet.get_lifetime()) {
+            if let Some(explosive) = explosive {
+                explosion::spawn_explosion(&mut commands, &images, transform.translation, explosive.radius, bullet.get_damage(), explosive.falloff, bullet.get_source());
+            }
+
             commands.entity(entity).despawn();
         }
     }
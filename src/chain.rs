@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::enemy::{self, Enemy};
+use crate::floating_text;
+use crate::game::{GameTimer, Health};
+use crate::random::GameRng;
+use crate::spatial::SpatialGrid;
+use crate::stats::CombatStats;
+use crate::tower::{EnemyQuery, Tower};
+use crate::veterancy::{self, DamageDealt};
+
+const ARC_LIFETIME: f32 = 0.15;
+const ARC_WIDTH: f32 = 3.0;
+
+/// Strikes its target with lightning that then jumps to up to `projectiles`
+/// more enemies, each within `jump_radius` of the last one hit. Every jump
+/// deals `falloff` times the damage of the one before it.
+#[derive(Component)]
+pub struct ChainTower {
+    pub jump_radius: f32,
+    pub falloff: f32,
+}
+
+impl ChainTower {
+    pub fn new(jump_radius: f32, falloff: f32) -> ChainTower {
+        return ChainTower {
+            jump_radius,
+            falloff,
+        };
+    }
+}
+
+/// One segment of a lightning strike, faded out and removed after `ARC_LIFETIME`.
+#[derive(Component)]
+struct ChainArc;
+
+pub struct ChainPlugin;
+impl Plugin for ChainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_chain_tower);
+        app.add_systems(Update, update_chain_arcs);
+    }
+}
+
+fn update_chain_tower(
+    mut commands: Commands,
+    mut tower_query: Query<(Entity, &mut Tower, &ChainTower, &mut Transform, &mut GameTimer, &mut CombatStats)>,
+    mut enemy_queries: ParamSet<(EnemyQuery, Query<&mut Health, With<Enemy>>)>,
+    grid: Res<SpatialGrid>,
+    mut rng: ResMut<GameRng>,
+    mut damage_events: EventWriter<DamageDealt>,
+    time: Res<Time>,
+) {
+    for (t_entity, mut tower, chain, mut transform, mut timer, mut stats) in tower_query.iter_mut() {
+        if (!tower.activated) {
+            continue;
+        }
+
+        let Some(target) = tower.update_target(transform.translation, &enemy_queries.p0(), &grid) else {
+            continue;
+        };
+
+        let target_pos = enemy_queries.p0().get(target).unwrap().1.translation;
+        tower.set_direction(Vec3::normalize(target_pos - transform.translation));
+        tower.rotate_towards(&mut transform, target_pos);
+
+        timer.add_time(time.delta_seconds());
+        if (timer.get_time() < tower.get_rate_of_fire()) {
+            continue;
+        }
+
+        let mut hits = vec![(target, target_pos)];
+        for _ in 0..tower.get_projectiles() {
+            let last = hits.last().unwrap().1;
+            let mut next: Option<(Entity, Vec3)> = None;
+
+            for (entity, _) in grid.query_radius(last.truncate(), chain.jump_radius) {
+                if (hits.iter().any(|(hit, _)| *hit == entity)) {
+                    continue;
+                }
+
+                let enemy_query = enemy_queries.p0();
+                let Ok((_, e_transform, _, health, stealth)) = enemy_query.get(entity) else {
+                    continue;
+                };
+                if (health.get_health() <= 0 || !enemy::visible_to_team(stealth)) {
+                    continue;
+                }
+
+                let position = e_transform.translation;
+                if (next.is_none_or(|(_, best)| Vec3::distance(last, position) < Vec3::distance(last, best))) {
+                    next = Some((entity, position));
+                }
+            }
+
+            let Some(next) = next else {
+                break;
+            };
+            hits.push(next);
+        }
+
+        stats.record_shots(1);
+        stats.record_hit();
+
+        let overcharge = tower.take_overcharge();
+        let (damage, crit) = tower.roll_damage(&mut rng, overcharge);
+        if (crit) {
+            floating_text::spawn_crit_text(&mut commands, damage, target_pos);
+        }
+
+        let mut damage = damage as f32;
+        let mut from = transform.translation;
+        for (entity, position) in hits {
+            if let Ok(mut health) = enemy_queries.p1().get_mut(entity) {
+                veterancy::deal_damage(&mut health, damage.round() as i32, Some(t_entity), &mut damage_events);
+            }
+
+            spawn_arc(&mut commands, from, position);
+            damage *= chain.falloff;
+            from = position;
+        }
+
+        timer.reset();
+    }
+}
+
+fn spawn_arc(commands: &mut Commands, start: Vec3, end: Vec3) {
+    let difference = end - start;
+    let mut transform = Transform::from_translation(Vec3::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0, 2.5));
+    transform.rotation = Quat::from_rotation_z(f32::atan2(difference.y, difference.x));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.75, 0.7, 1.0, 0.9),
+                custom_size: Some(Vec2::new(difference.truncate().length(), ARC_WIDTH)),
+                ..default()
+            },
+            transform,
+            visibility: Visibility::Visible,
+            ..default()
+        },
+        ChainArc,
+        GameTimer::new(0.0)
+    ));
+}
+
+fn update_chain_arcs(
+    mut commands: Commands,
+    mut arc_query: Query<(Entity, &mut Sprite, &mut GameTimer), With<ChainArc>>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut timer) in arc_query.iter_mut() {
+        timer.add_time(time.delta_seconds());
+        sprite.color.set_a(0.9 * (1.0 - timer.get_time() / ARC_LIFETIME));
+
+        if (timer.get_time() >= ARC_LIFETIME) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::ability::{AbilityDefinition, TowerAbility};
+use crate::beam::BeamTower;
+use crate::bullet::{Explosive, Homing, Pierce, Ricochet};
+use crate::chain::ChainTower;
+use crate::farm::FarmTower;
+use crate::frost::FrostTower;
+use crate::game::GameTimer;
+use crate::resources::Images;
+use crate::stats::CombatStats;
+use crate::support::{BuffStat, SupportTower};
+use crate::tower::{DetectionTower, DirectionalTower, RocketTower, Tower};
+use crate::upgrade::{UpgradePath, UpgradeTier};
+use crate::veterancy::Veterancy;
+
+const DEFINITIONS_PATH: &str = "assets/towers.ron";
+/// Bounce radius for projectiles that only get ricochets from an upgrade.
+const DEFAULT_RICOCHET_RADIUS: f32 = 120.0;
+
+/// How a tower attacks, or what it does instead, along with the settings of the
+/// component that gets added for it.
+#[derive(Deserialize, Clone, Copy)]
+pub enum TowerBehavior {
+    /// Aims at its target and fires a fan of `projectiles`.
+    Projectile,
+    /// Fires `projectiles` evenly spaced around itself.
+    Directional,
+    Rocket { radius: f32, falloff: f32 },
+    Frost { slow: f32, duration: f32 },
+    Beam { ramp_rate: f32, max_ramp: f32 },
+    Chain { jump_radius: f32, falloff: f32 },
+    Support { stat: BuffStat, amount: f32 },
+    Farm { income: i32 },
+    Detector,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ProjectileDefinition {
+    pub sprite: String,
+    pub speed: f32,
+    /// Seconds before the projectile expires. Defaults to just long enough to
+    /// reach the edge of the tower's range.
+    #[serde(default)]
+    pub lifetime: Option<f32>,
+    #[serde(default)]
+    pub homing: Option<Homing>,
+    #[serde(default)]
+    pub pierce: Option<Pierce>,
+    #[serde(default)]
+    pub ricochet: Option<Ricochet>,
+}
+
+impl ProjectileDefinition {
+    pub fn lifetime(&self, range: f32) -> f32 {
+        return self.lifetime.unwrap_or(range / self.speed + 0.25);
+    }
+
+    /// Adds the projectile's optional behaviors to a freshly spawned bullet.
+    pub fn insert_behaviors(&self, bullet: &mut EntityCommands, target: Option<Entity>) {
+        if let Some(homing) = self.homing {
+            bullet.insert(Homing { target, ..homing });
+        }
+        if let Some(pierce) = self.pierce {
+            bullet.insert(pierce);
+        }
+        if let Some(ricochet) = self.ricochet {
+            bullet.insert(ricochet);
+        }
+    }
+
+    pub fn upgrade(&mut self, tier: &UpgradeTier) {
+        if (tier.pierce != 0) {
+            self.pierce.get_or_insert(Pierce { count: 0 }).count += tier.pierce;
+        }
+        if (tier.bounces != 0) {
+            self.ricochet.get_or_insert(Ricochet { bounces: 0, radius: DEFAULT_RICOCHET_RADIUS }).bounces += tier.bounces;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TowerDefinition {
+    pub name: String,
+    pub sprite: String,
+    pub cost: i32,
+    pub range: f32,
+    #[serde(default)]
+    pub rate_of_fire: f32,
+    #[serde(default)]
+    pub damage: i32,
+    #[serde(default)]
+    pub projectiles: i32,
+    #[serde(default)]
+    pub projectile: Option<ProjectileDefinition>,
+    /// How well the tower leads moving targets, from 0.0 (aims where the enemy
+    /// is) to 1.0 (aims at the exact intercept).
+    #[serde(default = "full_accuracy")]
+    pub accuracy: f32,
+    /// Chance from 0.0 to 1.0 that a shot deals `crit_multiplier` times its damage.
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: f32,
+    /// Shots deal a random amount within this fraction above or below `damage`.
+    #[serde(default)]
+    pub damage_variance: f32,
+    pub behavior: TowerBehavior,
+    #[serde(default)]
+    pub ability: Option<AbilityDefinition>,
+    #[serde(default)]
+    pub upgrades: UpgradePath,
+}
+
+fn full_accuracy() -> f32 {
+    return 1.0;
+}
+
+fn default_crit_multiplier() -> f32 {
+    return 2.0;
+}
+
+/// Every tower the player can buy, loaded from `assets/towers.ron`. Towers refer
+/// to their definition by its index.
+#[derive(Resource, Deserialize)]
+pub struct TowerDefinitions {
+    pub towers: Vec<TowerDefinition>,
+}
+
+/// Reads a data file from the assets folder. These are needed before the game
+/// can start, so a missing or broken file is fatal.
+pub fn load_ron<T: DeserializeOwned>(file: &str) -> T {
+    let path = FileAssetReader::get_base_path().join(file);
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Couldn't read {}: {}", path.display(), e));
+
+    return ron::from_str(&text)
+        .unwrap_or_else(|e| panic!("Invalid data in {}: {}", path.display(), e));
+}
+
+impl TowerDefinitions {
+    pub fn load() -> TowerDefinitions {
+        return load_ron(DEFINITIONS_PATH);
+    }
+
+    /// Index of the tower with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        return self.towers.iter().position(|tower| tower.name == name);
+    }
+
+    pub fn get(&self, index: usize) -> &TowerDefinition {
+        return &self.towers[index];
+    }
+}
+
+pub struct DefinitionsPlugin;
+impl Plugin for DefinitionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TowerDefinitions::load());
+    }
+}
+
+/// Spawns an inactive tower from its definition, ready to be placed.
+pub fn spawn_tower(commands: &mut Commands, images: &Images, definitions: &TowerDefinitions, index: usize) -> Entity {
+    let definition = definitions.get(index);
+
+    let mut t = Transform::from_translation(Vec3::new(-16.0, -16.0, 3.0));
+    t.scale = Vec3::new(2.0, 2.0, 2.0);
+
+    let mut tower = commands.spawn((SpriteBundle {
+        transform: t,
+        texture: images.get(&definition.sprite),
+        visibility: Visibility::Visible,
+        ..default()
+    },
+        Tower::new(index, definition),
+        Veterancy::default(),
+        CombatStats::default(),
+        GameTimer::new(0.0))
+    );
+
+    if let Some(ability) = definition.ability.as_ref() {
+        tower.insert(TowerAbility::new(ability.clone()));
+    }
+
+    match definition.behavior {
+        TowerBehavior::Projectile => {}
+        TowerBehavior::Directional => {
+            tower.insert(DirectionalTower);
+        }
+        TowerBehavior::Rocket { radius, falloff } => {
+            tower.insert((RocketTower, Explosive::new(radius, falloff)));
+        }
+        TowerBehavior::Frost { slow, duration } => {
+            tower.insert(FrostTower::new(slow, duration));
+        }
+        TowerBehavior::Beam { ramp_rate, max_ramp } => {
+            tower.insert(BeamTower::new(ramp_rate, max_ramp));
+        }
+        TowerBehavior::Chain { jump_radius, falloff } => {
+            tower.insert(ChainTower::new(jump_radius, falloff));
+        }
+        TowerBehavior::Support { stat, amount } => {
+            tower.insert(SupportTower::new(stat, amount));
+        }
+        TowerBehavior::Farm { income } => {
+            tower.insert(FarmTower::new(income));
+        }
+        TowerBehavior::Detector => {
+            tower.insert(DetectionTower);
+        }
+    }
+
+    return tower.id();
+}
@@ -1,31 +1,104 @@
 use bevy::prelude::*;
-use bevy::sprite::collide_aabb::collide;
-use crate::bullet::Bullet;
-use crate::game::{Health, PlayerBase, PlayerStats, RoundInfo};
-use crate::level::Waypoints;
+use crate::animation::{AnimationClip, SpriteAnimation};
+use crate::bullet::{self, Bullet, Explosive, Homing, Pierce, Ricochet};
+use crate::explosion;
+use crate::floating_text;
+use crate::game::{GameTimer, Health, PlayerBase, PlayerStats, RoundInfo};
+use crate::level::{Waypoints, ENEMY_TYPES};
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::stats::ShotLanded;
+use crate::veterancy::{self, DamageDealt};
 
 const ENEMY_SIZE: Vec2 = Vec2::new(32.0, 32.0);
-const BULLET_SIZE: Vec2 = Vec2::new(24.0, 4.0);
+/// Bullets are treated as circles of this radius when they hit an enemy.
+const BULLET_RADIUS: f32 = 6.0;
 const ENEMY_SPAWN: Vec3 = Vec3::new(220.0, -84.0, 0.0);
+const STEALTH_REVEAL_TIME: f32 = 1.5;
+const MAX_SLOW: f32 = 0.7;
+const FREEZE_IMMUNITY: f32 = 3.0;
+
+/// Sprite sheet for an enemy type, laid out as a single row of frames.
+pub struct EnemySheet {
+    pub path: &'static str,
+    pub cell_size: Vec2,
+    pub columns: usize,
+    walk: AnimationClip,
+    death: AnimationClip,
+    fps: f32,
+}
+
+const fn enemy_sheet(path: &'static str) -> EnemySheet {
+    return EnemySheet {
+        path,
+        cell_size: Vec2::new(32.0, 32.0),
+        columns: 8,
+        walk: AnimationClip::new(0, 3),
+        death: AnimationClip::new(4, 7),
+        fps: 8.0,
+    };
+}
+
+const WEAK_SHEET: EnemySheet = enemy_sheet("enemies/weak");
+const FAST_SHEET: EnemySheet = enemy_sheet("enemies/fast");
+const MEDIUM_SHEET: EnemySheet = enemy_sheet("enemies/medium");
+const STRONG_SHEET: EnemySheet = enemy_sheet("enemies/strong");
+const SPLITTER_SHEET: EnemySheet = enemy_sheet("enemies/splitter");
+const HEALER_SHEET: EnemySheet = enemy_sheet("enemies/healer");
+const SHIELD_SHEET: EnemySheet = enemy_sheet("enemies/shield");
+const STEALTH_SHEET: EnemySheet = enemy_sheet("enemies/stealth");
+
+pub const ENEMY_SHEETS: [&EnemySheet; 8] = [
+    &WEAK_SHEET,
+    &FAST_SHEET,
+    &MEDIUM_SHEET,
+    &STRONG_SHEET,
+    &SPLITTER_SHEET,
+    &HEALER_SHEET,
+    &SHIELD_SHEET,
+    &STEALTH_SHEET,
+];
 
 #[derive(Component)]
 pub struct Enemy {
     pub speed: f32,
+    /// Speed the enemy moved at last frame, after slows.
+    pub current_speed: f32,
     pub waypoint_id: usize,
     pub direction: Vec3,
+    pub distance_traveled: f32,
+    pub distance_remaining: f32,
     damage: i32,
+    sheet: &'static EnemySheet,
 }
 
 impl Enemy {
-    pub fn new(spd: f32, dmg: i32) -> Enemy {
+    pub fn new(spd: f32, dmg: i32, sheet: &'static EnemySheet) -> Enemy {
         return Enemy {
             speed: spd,
+            current_speed: spd,
             waypoint_id: 0,
             direction: Vec3::ZERO,
-            damage: dmg
+            distance_traveled: 0.0,
+            distance_remaining: f32::INFINITY,
+            damage: dmg,
+            sheet,
         }
     }
 
+    pub fn with_spawn(mut self, spawn: EnemySpawn) -> Enemy {
+        self.waypoint_id = spawn.waypoint_id;
+        self.direction = spawn.direction;
+        self.distance_traveled = spawn.distance;
+        return self;
+    }
+
+    /// Damage the enemy does to the base when it gets through, and to the hero
+    /// every second it's in reach.
+    pub fn get_damage(&self) -> i32 {
+        return self.damage;
+    }
+
     fn rotate_towards(&self, transform: &mut Transform, point: Vec3) {
         let difference = point - transform.translation;
         let angle = f32::atan2(difference.y, difference.x);
@@ -33,134 +106,717 @@ impl Enemy {
     }
 }
 
+/// Where a newly spawned enemy starts along the route.
+#[derive(Clone, Copy)]
+pub struct EnemySpawn {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub waypoint_id: usize,
+    pub direction: Vec3,
+    pub distance: f32,
+}
+
+impl EnemySpawn {
+    pub fn start() -> EnemySpawn {
+        return EnemySpawn {
+            translation: ENEMY_SPAWN,
+            rotation: Quat::IDENTITY,
+            waypoint_id: 0,
+            direction: Vec3::ZERO,
+            distance: 0.0,
+        };
+    }
+
+    pub fn transform(&self) -> Transform {
+        let mut t = Transform::from_translation(self.translation);
+        t.rotation = self.rotation;
+        return t;
+    }
+}
+
+#[derive(Component)]
+pub struct Splitter {
+    count: i32,
+    child: usize,
+}
+
+impl Splitter {
+    pub fn new(count: i32, child: usize) -> Splitter {
+        return Splitter {
+            count,
+            child
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct Healer {
+    radius: f32,
+    amount: i32,
+    interval: f32,
+}
+
+impl Healer {
+    pub fn new(radius: f32, amount: i32, interval: f32) -> Healer {
+        return Healer {
+            radius,
+            amount,
+            interval
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct ShieldProjector {
+    radius: f32,
+    amount: i32,
+    interval: f32,
+}
+
+impl ShieldProjector {
+    pub fn new(radius: f32, amount: i32, interval: f32) -> ShieldProjector {
+        return ShieldProjector {
+            radius,
+            amount,
+            interval
+        };
+    }
+}
+
+/// Movement slow on an enemy. Slows don't stack, the strongest active one is
+/// used and slows alone can never stop an enemy. A freeze stops it completely
+/// but leaves it immune to freezes for a while afterwards.
+#[derive(Component, Default)]
+pub struct Slow {
+    amount: f32,
+    remaining: f32,
+    frozen: f32,
+    freeze_immunity: f32,
+}
+
+impl Slow {
+    pub fn apply(&mut self, amount: f32, duration: f32) {
+        let amount = f32::min(amount, MAX_SLOW);
+
+        if (self.remaining <= 0.0 || amount >= self.amount) {
+            self.amount = amount;
+            self.remaining = f32::max(self.remaining, duration);
+        }
+    }
+
+    pub fn freeze(&mut self, duration: f32) {
+        if (self.freeze_immunity > 0.0 || duration <= 0.0) {
+            return;
+        }
+
+        self.frozen = duration;
+        self.freeze_immunity = duration + FREEZE_IMMUNITY;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        return self.frozen > 0.0;
+    }
+
+    pub fn is_slowed(&self) -> bool {
+        return self.remaining > 0.0;
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if (self.is_frozen()) {
+            return 0.0;
+        }
+
+        if (self.is_slowed()) {
+            return 1.0 - self.amount;
+        }
+
+        return 1.0;
+    }
+
+    fn tick(&mut self, delta: f32) {
+        self.remaining = f32::max(self.remaining - delta, 0.0);
+        self.frozen = f32::max(self.frozen - delta, 0.0);
+        self.freeze_immunity = f32::max(self.freeze_immunity - delta, 0.0);
+    }
+}
+
+/// Camouflaged enemies can only be targeted while inside a detection tower's aura
+/// or for a short while after being revealed by an effect.
+#[derive(Component)]
+pub struct Stealth {
+    pub detected: bool,
+    revealed_time: f32,
+}
+
+impl Stealth {
+    pub fn new() -> Stealth {
+        return Stealth {
+            detected: false,
+            revealed_time: 0.0,
+        };
+    }
+
+    pub fn reveal(&mut self, duration: f32) {
+        self.revealed_time = f32::max(self.revealed_time, duration);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        return self.detected || self.revealed_time > 0.0;
+    }
+}
+
+pub fn visible_to_team(stealth: Option<&Stealth>) -> bool {
+    return stealth.is_none_or(|s| s.is_visible());
+}
+
+/// Whether an enemy is still alive and visible, so it can be aimed at.
+pub fn is_targetable(health: &Health, stealth: Option<&Stealth>) -> bool {
+    return health.get_health() > 0 && visible_to_team(stealth);
+}
+
 pub struct EnemyPlugin;
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, move_enemy);
-        app.add_systems(Update, bullet_collision);
+        app.init_resource::<SpatialGrid>();
+        app.add_systems(PreUpdate, rebuild_enemy_grid);
+        app.add_systems(Update, bullet_collision.after(bullet::update_bullets));
+        app.add_systems(Update, enemy_death.after(bullet_collision));
+        app.add_systems(Update, move_enemy.after(enemy_death));
+        app.add_systems(Update, heal_enemies);
+        app.add_systems(Update, project_shields);
+        app.add_systems(Update, update_stealth);
+        app.add_systems(Update, tint_slowed);
+        app.add_systems(Update, remove_dead_enemies);
     }
 }
 
+/// Marks an enemy that was killed and is playing its death animation.
+#[derive(Component)]
+pub struct Dying;
+
 #[derive(Bundle)]
 pub struct EnemyBundle {
     enemy: Enemy,
-    sprite_bundle: SpriteBundle,
+    sprite_sheet_bundle: SpriteSheetBundle,
+    animation: SpriteAnimation,
     health: Health,
+    slow: Slow,
+}
+
+impl EnemyBundle {
+    pub fn new(enemy: Enemy, health: Health, images: &Images, transform: Transform) -> EnemyBundle {
+        let sheet = enemy.sheet;
+
+        return EnemyBundle {
+            enemy,
+            sprite_sheet_bundle: SpriteSheetBundle {
+                sprite: TextureAtlasSprite::new(sheet.walk.first()),
+                texture_atlas: images.atlas(sheet.path),
+                transform,
+                visibility: Visibility::Visible,
+                ..default()
+            },
+            animation: SpriteAnimation::new(sheet.walk, sheet.fps, true),
+            health,
+            slow: Slow::default(),
+        };
+    }
 }
 
 fn move_enemy(
     mut commands: Commands,
-    mut query: Query<(&mut Enemy, &mut Transform, Entity)>,
+    mut query: Query<(&mut Enemy, &mut Transform, &mut Slow, Entity)>,
     waypoints: Query<&Waypoints>,
     time: Res<Time>,
     mut round_info: ResMut<RoundInfo>,
     mut base_query: Query<&mut Health, With<PlayerBase>>,
 ) {
-    for (mut enemy, mut transform, entity) in query.iter_mut() {
-        let next_point = waypoints.single().points[enemy.waypoint_id];
+    let path = waypoints.single();
+    let total_length = path.total_length();
+
+    for (mut enemy, mut transform, mut slow, entity) in query.iter_mut() {
+        slow.tick(time.delta_seconds());
+        enemy.current_speed = enemy.speed * slow.speed_multiplier();
+        enemy.distance_traveled += enemy.current_speed * time.delta_seconds();
+        enemy.distance_remaining = f32::max(total_length - enemy.distance_traveled, 0.0);
+
+        if (enemy.distance_traveled >= total_length) {
+            let mut base_health = base_query.get_single_mut().unwrap();
+            base_health.lose(enemy.damage);
+
+            commands.entity(entity).despawn();
+            round_info.enemies_killed += 1;
+            continue;
+        }
+
+        let (position, dir, waypoint_id) = path.sample(enemy.distance_traveled);
+        transform.translation = Vec3::new(position.x, position.y, transform.translation.z);
+        enemy.direction = dir;
+        enemy.waypoint_id = waypoint_id;
+        enemy.rotate_towards(&mut transform, position + dir);
+    }
+}
+
+/// Refills the grid with every living enemy before anything this frame moves,
+/// damages or targets them.
+fn rebuild_enemy_grid(
+    mut grid: ResMut<SpatialGrid>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    grid.clear();
 
-        let dir = Vec3::normalize(waypoints.single().points[enemy.waypoint_id] - transform.translation);
-        let dist = Vec3::distance(transform.translation, next_point);
+    for (entity, transform) in enemy_query.iter() {
+        grid.insert(entity, transform.translation.truncate());
+    }
+}
 
-        if (dist <= 6.0) {
-            if (enemy.waypoint_id < waypoints.single().points.len() - 1usize) {
-                enemy.waypoint_id += 1;
-            } else {
-                let mut base_health = base_query.get_single_mut().unwrap();
-                base_health.lose(enemy.damage);
+/// Fraction of the way from `start` to `end` where the segment enters the box,
+/// or `None` if it misses. A segment starting inside the box hits at 0.0.
+fn sweep_box(start: Vec2, end: Vec2, center: Vec2, half_size: Vec2) -> Option<f32> {
+    let min = center - half_size;
+    let max = center + half_size;
+    let delta = end - start;
 
-                commands.entity(entity).despawn();
-                round_info.enemies_killed += 1;
+    let mut t_enter: f32 = 0.0;
+    let mut t_exit: f32 = 1.0;
+
+    for axis in 0..2 {
+        if (delta[axis].abs() <= f32::EPSILON) {
+            if (start[axis] < min[axis] || start[axis] > max[axis]) {
+                return None;
             }
-        } else {
-            transform.translation += dir * enemy.speed * time.delta_seconds();
-            enemy.direction = dir;
-            enemy.rotate_towards(&mut transform, next_point);
+            continue;
         }
 
-        //println!("Enemy position: {}, {}, {} at waypoint {}", enemy.1.translation.x, enemy.1.translation.y, enemy.1.translation.z, enemy.0.waypoint_id);
+        let t1 = (min[axis] - start[axis]) / delta[axis];
+        let t2 = (max[axis] - start[axis]) / delta[axis];
+        t_enter = f32::max(t_enter, f32::min(t1, t2));
+        t_exit = f32::min(t_exit, f32::max(t1, t2));
+
+        if (t_enter > t_exit) {
+            return None;
+        }
     }
+
+    return Some(t_enter);
 }
 
+type BulletQuery<'w, 's> = Query<'w, 's, (Entity, &'static mut Transform, &'static mut Bullet, &'static mut GameTimer, Option<&'static Explosive>, Option<&'static mut Pierce>, Option<&'static mut Ricochet>, Option<&'static mut Homing>), Without<Enemy>>;
+
+/// Sweeps each bullet along the path it moved this frame, so hits don't depend
+/// on the frame rate. Enemies are hit in the order the bullet reaches them.
 fn bullet_collision(
     mut commands: Commands,
-    mut enemy_query: Query<(Entity, &Transform, &mut Health), With<Enemy>>,
-    bullet_query: Query<(Entity, &Transform, &Bullet)>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Health, Option<&mut Stealth>), With<Enemy>>,
+    mut bullet_query: BulletQuery,
     mut player_stats: ResMut<PlayerStats>,
-    mut round_info: ResMut<RoundInfo>,
+    images: Res<Images>,
+    grid: Res<SpatialGrid>,
+    mut damage_events: EventWriter<DamageDealt>,
+    mut shot_events: EventWriter<ShotLanded>,
 ) {
-    for (e_entity, enemy, mut health) in enemy_query.iter_mut() {
-        for (b_entity, b_transform, bullet) in bullet_query.iter() {
-            let collision = collide(enemy.translation, ENEMY_SIZE, b_transform.translation, BULLET_SIZE);
-            
-            if let Some(collision) = collision {
-                commands.entity(b_entity).despawn();
+    let half_size = ENEMY_SIZE / 2.0 + Vec2::splat(BULLET_RADIUS);
 
-                health.lose(bullet.get_damage());
-                if (health.get_health() <= 0) {
-                    commands.entity(e_entity).despawn();
-                    round_info.enemies_killed += 1;
-                    player_stats.add_coins(5);
-                    continue;
-                }
+    for (b_entity, mut b_transform, mut bullet, mut timer, explosive, mut pierce, mut ricochet, mut homing) in bullet_query.iter_mut() {
+        let (start, end) = bullet.segment(&b_transform);
+        let search_radius = start.distance(end) / 2.0 + half_size.length();
+
+        let mut hits: Vec<(f32, Entity)> = Vec::new();
+        for (e_entity, _) in grid.query_radius((start + end) / 2.0, search_radius) {
+            if (bullet.has_hit(e_entity)) {
+                continue;
+            }
+
+            let Ok((_, e_transform, health, _)) = enemy_query.get(e_entity) else {
+                continue;
+            };
+            if (health.get_health() <= 0) {
+                continue;
+            }
+
+            if let Some(t) = sweep_box(start, end, e_transform.translation.truncate(), half_size) {
+                hits.push((t, e_entity));
+            }
+        }
+        hits.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        if let Some(tower) = bullet.get_source().filter(|_| !hits.is_empty() && !bullet.has_landed()) {
+            shot_events.send(ShotLanded { tower });
+        }
+
+        for (t, e_entity) in hits {
+            let (_, _, mut health, mut stealth) = enemy_query.get_mut(e_entity).unwrap();
+            let point = start.lerp(end, t).extend(b_transform.translation.z);
+            if (bullet.is_crit()) {
+                floating_text::spawn_crit_text(&mut commands, bullet.get_damage(), point);
+            }
+
+            if let Some(explosive) = explosive {
+                commands.entity(b_entity).despawn();
+                explosion::spawn_explosion(&mut commands, &images, point, explosive.radius, bullet.get_damage(), explosive.falloff, bullet.get_source());
+                break;
+            }
 
+            bullet.record_hit(e_entity);
+            veterancy::deal_damage(&mut health, bullet.get_damage(), bullet.get_source(), &mut damage_events);
+            if let Some(stealth) = stealth.as_mut() {
+                stealth.reveal(STEALTH_REVEAL_TIME);
+            }
+            if (health.get_health() > 0) {
                 player_stats.add_coins(1);
             }
+
+            // Ricochets leave from the point of impact, so the rest of this
+            // frame's path no longer applies to them.
+            let direction = bullet.get_direction();
+            b_transform.translation = point;
+            // The grid is only rebuilt once a frame, so it can still hold
+            // enemies that were killed since.
+            let bounce_targets = |position, radius| grid.query_radius(position, radius).into_iter()
+                .filter(|(entity, _)| enemy_query.get(*entity).is_ok_and(|(_, _, health, stealth)| is_targetable(health, stealth)))
+                .collect();
+            if (!bullet::continue_after_hit(&mut bullet, &mut b_transform, &mut timer, pierce.as_deref_mut(), ricochet.as_deref_mut(), homing.as_deref_mut(), bounce_targets)) {
+                commands.entity(b_entity).despawn();
+                break;
+            }
+            if (bullet.get_direction() != direction) {
+                break;
+            }
+            b_transform.translation = end.extend(point.z);
+        }
+    }
+}
+
+type DeathQuery<'w, 's> = Query<'w, 's, (Entity, &'static Enemy, &'static Transform, &'static Health, &'static mut SpriteAnimation, Option<&'static Splitter>)>;
+
+fn enemy_death(
+    mut commands: Commands,
+    mut enemy_query: DeathQuery,
+    mut player_stats: ResMut<PlayerStats>,
+    mut round_info: ResMut<RoundInfo>,
+    images: Res<Images>,
+) {
+    for (entity, enemy, transform, health, mut animation, splitter) in enemy_query.iter_mut() {
+        if (health.get_health() > 0) {
+            continue;
+        }
+
+        animation.play(enemy.sheet.death, false);
+        commands.entity(entity).remove::<(Enemy, Health)>().insert(Dying);
+        round_info.enemies_killed += 1;
+        player_stats.add_coins(5);
+
+        if let Some(splitter) = splitter {
+            for i in 0..splitter.count {
+                let offset = f32::min(12.0 * i as f32, enemy.distance_traveled);
+                let spawn = EnemySpawn {
+                    translation: transform.translation - enemy.direction * offset,
+                    rotation: transform.rotation,
+                    waypoint_id: enemy.waypoint_id,
+                    direction: enemy.direction,
+                    distance: enemy.distance_traveled - offset,
+                };
+                ENEMY_TYPES[splitter.child](&mut commands, &images, spawn);
+            }
+
+            round_info.enemies_split += splitter.count;
+        }
+    }
+}
+
+fn heal_enemies(
+    mut healer_query: Query<(Entity, &Healer, &Transform, &mut GameTimer), With<Enemy>>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Health), With<Enemy>>,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+) {
+    for (h_entity, healer, h_transform, mut timer) in healer_query.iter_mut() {
+        timer.add_time(time.delta_seconds());
+        if (timer.get_time() < healer.interval) {
+            continue;
+        }
+
+        for (entity, _) in grid.query_radius(h_transform.translation.truncate(), healer.radius) {
+            let Ok((_, transform, mut health)) = enemy_query.get_mut(entity) else {
+                continue;
+            };
+            if (entity == h_entity || health.get_health() <= 0) {
+                continue;
+            }
+
+            if (Vec3::distance(h_transform.translation, transform.translation) <= healer.radius) {
+                health.add(healer.amount);
+            }
+        }
+
+        timer.reset();
+    }
+}
+
+fn project_shields(
+    mut projector_query: Query<(Entity, &ShieldProjector, &Transform, &mut GameTimer), With<Enemy>>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Health), With<Enemy>>,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+) {
+    for (p_entity, projector, p_transform, mut timer) in projector_query.iter_mut() {
+        timer.add_time(time.delta_seconds());
+        if (timer.get_time() < projector.interval) {
+            continue;
+        }
+
+        for (entity, _) in grid.query_radius(p_transform.translation.truncate(), projector.radius) {
+            let Ok((_, transform, mut health)) = enemy_query.get_mut(entity) else {
+                continue;
+            };
+            if (entity == p_entity || health.get_health() <= 0) {
+                continue;
+            }
+
+            if (Vec3::distance(p_transform.translation, transform.translation) <= projector.radius) {
+                health.add_shield(projector.amount);
+            }
+        }
+
+        timer.reset();
+    }
+}
+
+fn update_stealth(
+    mut stealth_query: Query<(&mut Stealth, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut stealth, mut sprite) in stealth_query.iter_mut() {
+        stealth.revealed_time = f32::max(stealth.revealed_time - time.delta_seconds(), 0.0);
+
+        if (stealth.is_visible()) {
+            sprite.color.set_a(0.75);
+        } else {
+            sprite.color.set_a(0.3);
+        }
+    }
+}
+
+fn tint_slowed(
+    mut query: Query<(&Slow, &mut TextureAtlasSprite), With<Enemy>>,
+) {
+    for (slow, mut sprite) in query.iter_mut() {
+        let alpha = sprite.color.a();
+
+        if (slow.is_frozen()) {
+            sprite.color = Color::rgba(0.5, 0.8, 1.0, alpha);
+        } else if (slow.is_slowed()) {
+            sprite.color = Color::rgba(0.75, 0.9, 1.0, alpha);
+        } else {
+            sprite.color = Color::rgba(1.0, 1.0, 1.0, alpha);
+        }
+    }
+}
+
+fn remove_dead_enemies(
+    mut commands: Commands,
+    query: Query<(Entity, &SpriteAnimation), With<Dying>>,
+) {
+    for (entity, animation) in query.iter() {
+        if (animation.finished()) {
+            commands.entity(entity).despawn();
         }
     }
 }
 
-pub fn weak_enemy(image: Handle<Image>) -> EnemyBundle {
-    let mut t = Transform::from_translation(ENEMY_SPAWN);
+pub fn weak_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    let mut t = spawn.transform();
     t.scale = Vec3::new(1.5, 1.5, 1.5);
 
-    return EnemyBundle {
-        enemy: Enemy::new(150.0, 1),
-        sprite_bundle: SpriteBundle {
-            transform: t,
-            texture: image,
-            visibility: Visibility::Visible,
-            ..Default::default()
-        },
-        health: Health::new(30),
-    }
-}
-
-pub fn fast_enemy(image: Handle<Image>) -> EnemyBundle {
-    return EnemyBundle {
-        enemy: Enemy::new(320.0, 1),
-        sprite_bundle: SpriteBundle {
-            transform: Transform::from_translation(ENEMY_SPAWN),
-            texture: image,
-            visibility: Visibility::Visible,
-            ..Default::default()
-        },
-        health: Health::new(20),
-    }
-}
-
-pub fn medium_enemy(image: Handle<Image>) -> EnemyBundle {
-    return EnemyBundle {
-        enemy: Enemy::new(200.0, 2),
-        sprite_bundle: SpriteBundle {
-            transform: Transform::from_translation(ENEMY_SPAWN),
-            texture: image,
-            visibility: Visibility::Visible,
-            ..Default::default()
-        },
-        health: Health::new(60),
-    }
-}
-
-pub fn strong_enemy(image: Handle<Image>) -> EnemyBundle {
-    return EnemyBundle {
-        enemy: Enemy::new(210.0, 3),
-        sprite_bundle: SpriteBundle {
-            transform: Transform::from_translation(ENEMY_SPAWN),
-            texture: image,
-            visibility: Visibility::Visible,
-            ..Default::default()
-        },
-        health: Health::new(100),
-    }
-}
\ No newline at end of file
+    return commands.spawn(EnemyBundle::new(
+        Enemy::new(150.0, 1, &WEAK_SHEET).with_spawn(spawn),
+        Health::new(30),
+        images,
+        t
+    )).id();
+}
+
+pub fn fast_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn(EnemyBundle::new(
+        Enemy::new(320.0, 1, &FAST_SHEET).with_spawn(spawn),
+        Health::new(20),
+        images,
+        spawn.transform()
+    )).id();
+}
+
+pub fn medium_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn(EnemyBundle::new(
+        Enemy::new(200.0, 2, &MEDIUM_SHEET).with_spawn(spawn),
+        Health::new(60),
+        images,
+        spawn.transform()
+    )).id();
+}
+
+pub fn strong_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn(EnemyBundle::new(
+        Enemy::new(210.0, 3, &STRONG_SHEET).with_spawn(spawn),
+        Health::new(100),
+        images,
+        spawn.transform()
+    )).id();
+}
+
+pub fn splitting_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn((
+        EnemyBundle::new(
+            Enemy::new(140.0, 2, &SPLITTER_SHEET).with_spawn(spawn),
+            Health::new(80),
+            images,
+            spawn.transform()
+        ),
+        Splitter::new(3, 1)
+    )).id();
+}
+
+pub fn healer_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn((
+        EnemyBundle::new(
+            Enemy::new(170.0, 2, &HEALER_SHEET).with_spawn(spawn),
+            Health::new(70),
+            images,
+            spawn.transform()
+        ),
+        Healer::new(140.0, 15, 2.5),
+        GameTimer::new(0.0)
+    )).id();
+}
+
+pub fn shield_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn((
+        EnemyBundle::new(
+            Enemy::new(170.0, 2, &SHIELD_SHEET).with_spawn(spawn),
+            Health::new(70),
+            images,
+            spawn.transform()
+        ),
+        ShieldProjector::new(120.0, 20, 4.0),
+        GameTimer::new(0.0)
+    )).id();
+}
+
+pub fn stealth_enemy(commands: &mut Commands, images: &Images, spawn: EnemySpawn) -> Entity {
+    return commands.spawn((
+        EnemyBundle::new(
+            Enemy::new(230.0, 2, &STEALTH_SHEET).with_spawn(spawn),
+            Health::new(50),
+            images,
+            spawn.transform()
+        ),
+        Stealth::new()
+    )).id();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_is_capped() {
+        let mut slow = Slow::default();
+        slow.apply(0.95, 2.0);
+
+        assert_eq!(slow.speed_multiplier(), 1.0 - MAX_SLOW);
+    }
+
+    #[test]
+    fn weaker_slow_does_not_replace_stronger_one() {
+        let mut slow = Slow::default();
+        slow.apply(0.5, 2.0);
+        slow.apply(0.2, 5.0);
+
+        assert_eq!(slow.speed_multiplier(), 0.5);
+        slow.tick(2.5);
+        assert!(!slow.is_slowed());
+        assert_eq!(slow.speed_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn stronger_slow_with_shorter_duration_keeps_longer_duration() {
+        let mut slow = Slow::default();
+        slow.apply(0.2, 5.0);
+        slow.apply(0.5, 1.0);
+
+        assert_eq!(slow.speed_multiplier(), 0.5);
+        slow.tick(4.0);
+        assert_eq!(slow.speed_multiplier(), 0.5);
+        slow.tick(1.5);
+        assert_eq!(slow.speed_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn repeated_slows_never_stop_an_enemy() {
+        let mut slow = Slow::default();
+        for _ in 0..10 {
+            slow.apply(0.6, 1.0);
+            slow.tick(0.1);
+        }
+
+        assert!(slow.speed_multiplier() > 0.0);
+    }
+
+    #[test]
+    fn refreeze_during_immunity_is_ignored() {
+        let mut slow = Slow::default();
+        slow.freeze(1.0);
+        assert_eq!(slow.speed_multiplier(), 0.0);
+
+        slow.tick(1.5);
+        assert!(!slow.is_frozen());
+        slow.freeze(1.0);
+        assert!(!slow.is_frozen());
+
+        slow.tick(FREEZE_IMMUNITY);
+        slow.freeze(1.0);
+        assert!(slow.is_frozen());
+    }
+
+    #[test]
+    fn long_segment_passes_through_box() {
+        let half_size = ENEMY_SIZE / 2.0;
+        let t = sweep_box(Vec2::new(-500.0, 0.0), Vec2::new(500.0, 0.0), Vec2::ZERO, half_size);
+
+        assert_eq!(t, Some(484.0 / 1000.0));
+    }
+
+    #[test]
+    fn zero_length_segment() {
+        let half_size = ENEMY_SIZE / 2.0;
+
+        assert_eq!(sweep_box(Vec2::new(5.0, -5.0), Vec2::new(5.0, -5.0), Vec2::ZERO, half_size), Some(0.0));
+        assert_eq!(sweep_box(Vec2::new(40.0, 0.0), Vec2::new(40.0, 0.0), Vec2::ZERO, half_size), None);
+    }
+
+    #[test]
+    fn axis_parallel_miss() {
+        let half_size = ENEMY_SIZE / 2.0;
+
+        assert_eq!(sweep_box(Vec2::new(-100.0, 20.0), Vec2::new(100.0, 20.0), Vec2::ZERO, half_size), None);
+        assert_eq!(sweep_box(Vec2::new(-20.0, -100.0), Vec2::new(-20.0, 100.0), Vec2::ZERO, half_size), None);
+    }
+
+    #[test]
+    fn segment_stopping_short_misses() {
+        let half_size = ENEMY_SIZE / 2.0;
+
+        assert_eq!(sweep_box(Vec2::new(-100.0, 0.0), Vec2::new(-20.0, 0.0), Vec2::ZERO, half_size), None);
+    }
+
+    #[test]
+    fn nearer_box_is_entered_first() {
+        let half_size = ENEMY_SIZE / 2.0;
+        let start = Vec2::new(-200.0, 10.0);
+        let end = Vec2::new(200.0, -10.0);
+
+        let near = sweep_box(start, end, Vec2::new(-50.0, 0.0), half_size).unwrap();
+        let far = sweep_box(start, end, Vec2::new(60.0, 0.0), half_size).unwrap();
+
+        assert!(near < far);
+    }
+}
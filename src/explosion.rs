@@ -1,12 +1,26 @@
 use bevy::prelude::*;
 
-use crate::game::GameTimer;
+use crate::animation::{AnimationClip, SpriteAnimation};
+use crate::enemy::{Enemy, Stealth};
+use crate::game::{GameTimer, Health};
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::veterancy::{self, DamageDealt};
+
+pub const EXPLOSION_FRAMES: usize = 6;
+const EXPLOSION_FPS: f32 = 15.0;
+const EXPLOSION_REVEAL_TIME: f32 = 1.0;
+pub const EXPLOSION_SIZE: Vec2 = Vec2::new(64.0, 64.0);
 
 #[derive(Component)]
 pub struct Explosion {
     radius: f32,
     lifetime: f32,
-    damaged: bool
+    damaged: bool,
+    damage: i32,
+    falloff: f32,
+    /// The tower credited with the explosion's damage.
+    source: Option<Entity>,
 }
 
 impl Explosion {
@@ -14,9 +28,30 @@ impl Explosion {
         return Explosion {
             radius,
             lifetime,
-            damaged: false
+            damaged: false,
+            damage: 0,
+            falloff: 0.0,
+            source: None,
         };
     }
+
+    /// `falloff` is how much of the damage is lost at the edge of the radius,
+    /// from 0.0 (none) to 1.0 (no damage at the edge).
+    pub fn with_damage(mut self, damage: i32, falloff: f32) -> Explosion {
+        self.damage = damage;
+        self.falloff = falloff;
+        return self;
+    }
+
+    pub fn with_source(mut self, tower: Option<Entity>) -> Explosion {
+        self.source = tower;
+        return self;
+    }
+
+    pub fn damage_at(&self, distance: f32) -> i32 {
+        let scale = 1.0 - self.falloff * f32::clamp(distance / self.radius, 0.0, 1.0);
+        return (self.damage as f32 * scale).round() as i32;
+    }
 }
 
 pub struct ExplosionPlugin;
@@ -26,16 +61,56 @@ impl Plugin for ExplosionPlugin {
     }
 }
 
+pub fn spawn_explosion(commands: &mut Commands, images: &Images, position: Vec3, radius: f32, damage: i32, falloff: f32, source: Option<Entity>) {
+    let mut transform = Transform::from_translation(Vec3::new(position.x, position.y, 2.0));
+    transform.scale = Vec3::splat(radius * 2.0 / EXPLOSION_SIZE.x);
+
+    commands.spawn((
+        Explosion::new(radius, EXPLOSION_FRAMES as f32 / EXPLOSION_FPS).with_damage(damage, falloff).with_source(source),
+        SpriteSheetBundle {
+            sprite: TextureAtlasSprite::new(0),
+            texture_atlas: images.atlas("explosion"),
+            transform,
+            visibility: Visibility::Visible,
+            ..default()
+        },
+        SpriteAnimation::new(AnimationClip::new(0, EXPLOSION_FRAMES - 1usize), EXPLOSION_FPS, false),
+        GameTimer::new(0.0)
+    ));
+}
+
 fn update_explosions(
-    mut explosion_query: Query<(Entity, &Explosion, &mut GameTimer)>,
+    mut explosion_query: Query<(Entity, &mut Explosion, &Transform, &mut GameTimer)>,
+    mut enemy_query: Query<(&Transform, &mut Health, Option<&mut Stealth>), With<Enemy>>,
+    grid: Res<SpatialGrid>,
     time: Res<Time>,
     mut commands: Commands,
+    mut damage_events: EventWriter<DamageDealt>,
 ) {
-    for (entity, explosion, mut timer) in explosion_query.iter_mut() {
+    for (entity, mut explosion, transform, mut timer) in explosion_query.iter_mut() {
+        if (!explosion.damaged) {
+            for (e_entity, _) in grid.query_radius(transform.translation.truncate(), explosion.radius) {
+                let Ok((e_transform, mut health, stealth)) = enemy_query.get_mut(e_entity) else {
+                    continue;
+                };
+                let distance = Vec2::distance(transform.translation.truncate(), e_transform.translation.truncate());
+                if (distance > explosion.radius || health.get_health() <= 0) {
+                    continue;
+                }
+
+                veterancy::deal_damage(&mut health, explosion.damage_at(distance), explosion.source, &mut damage_events);
+                if let Some(mut stealth) = stealth {
+                    stealth.reveal(EXPLOSION_REVEAL_TIME);
+                }
+            }
+
+            explosion.damaged = true;
+        }
+
         timer.add_time(time.delta_seconds());
 
         if (timer.get_time() >= explosion.lifetime) {
             commands.entity(entity).despawn();
         }
     }
-}
\ No newline at end of file
+}
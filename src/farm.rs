@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::floating_text::spawn_floating_text;
+use crate::game::{PlayerStats, RoundCompleted};
+use crate::tower::Tower;
+
+const PAYOUT_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+/// Doesn't shoot, pays out `income` coins every time a round is cleared.
+#[derive(Component)]
+pub struct FarmTower {
+    pub income: i32,
+    pub earned: i32,
+}
+
+impl FarmTower {
+    pub fn new(income: i32) -> FarmTower {
+        return FarmTower {
+            income,
+            earned: 0,
+        };
+    }
+}
+
+pub struct FarmPlugin;
+impl Plugin for FarmPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, pay_farms);
+    }
+}
+
+fn pay_farms(
+    mut commands: Commands,
+    mut round_completed: EventReader<RoundCompleted>,
+    mut farm_query: Query<(&Tower, &mut FarmTower, &Transform)>,
+    mut player_stats: ResMut<PlayerStats>,
+) {
+    for _ in round_completed.read() {
+        for (tower, mut farm, transform) in farm_query.iter_mut() {
+            if (!tower.activated) {
+                continue;
+            }
+
+            player_stats.add_coins(farm.income);
+            farm.earned += farm.income;
+            spawn_floating_text(&mut commands, format!("+{}", farm.income), transform.translation + Vec3::new(0.0, 20.0, 0.0), PAYOUT_COLOR, 20.0);
+        }
+    }
+}
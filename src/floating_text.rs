@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::game::GameTimer;
+
+const FLOAT_SPEED: f32 = 40.0;
+const FLOAT_LIFETIME: f32 = 1.0;
+const CRIT_COLOR: Color = Color::rgb(1.0, 0.35, 0.2);
+const CRIT_SIZE: f32 = 26.0;
+
+/// World-space text that drifts upwards and fades out, used for payouts and
+/// critical hits.
+#[derive(Component)]
+pub struct FloatingText {
+    lifetime: f32,
+}
+
+pub struct FloatingTextPlugin;
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_floating_text);
+    }
+}
+
+pub fn spawn_floating_text(commands: &mut Commands, text: String, position: Vec3, color: Color, size: f32) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(text, TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::new(position.x, position.y, 5.0)),
+            ..default()
+        },
+        FloatingText { lifetime: FLOAT_LIFETIME },
+        GameTimer::new(0.0)
+    ));
+}
+
+/// Shows the damage of a critical hit above where it landed.
+pub fn spawn_crit_text(commands: &mut Commands, damage: i32, position: Vec3) {
+    spawn_floating_text(commands, format!("{}!", damage), position + Vec3::new(0.0, 16.0, 0.0), CRIT_COLOR, CRIT_SIZE);
+}
+
+fn update_floating_text(
+    mut commands: Commands,
+    mut text_query: Query<(Entity, &FloatingText, &mut Text, &mut Transform, &mut GameTimer)>,
+    time: Res<Time>,
+) {
+    for (entity, floating, mut text, mut transform, mut timer) in text_query.iter_mut() {
+        timer.add_time(time.delta_seconds());
+        transform.translation.y += FLOAT_SPEED * time.delta_seconds();
+
+        let alpha = 1.0 - timer.get_time() / floating.lifetime;
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+
+        if (timer.get_time() >= floating.lifetime) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::enemy::{Enemy, Slow};
+use crate::game::{GameTimer, Health};
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::stats::CombatStats;
+use crate::tower::Tower;
+use crate::veterancy::{self, DamageDealt};
+
+const PULSE_LIFETIME: f32 = 0.3;
+
+type SlowableQuery<'w, 's> = Query<'w, 's, (&'static Transform, &'static mut Slow, &'static mut Health), (With<Enemy>, Without<Tower>)>;
+
+/// Doesn't shoot, slows every enemy in range each time it fires.
+#[derive(Component)]
+pub struct FrostTower {
+    pub slow: f32,
+    pub duration: f32,
+    pub freeze: f32,
+}
+
+impl FrostTower {
+    pub fn new(slow: f32, duration: f32) -> FrostTower {
+        return FrostTower {
+            slow,
+            duration,
+            freeze: 0.0,
+        };
+    }
+}
+
+#[derive(Component)]
+struct FrostPulse;
+
+pub struct FrostPlugin;
+impl Plugin for FrostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_frost_tower);
+        app.add_systems(Update, update_frost_pulses);
+    }
+}
+
+fn update_frost_tower(
+    mut commands: Commands,
+    mut tower_query: Query<(Entity, &Tower, &FrostTower, &Transform, &mut GameTimer, &mut CombatStats)>,
+    mut enemy_query: SlowableQuery,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+    images: Res<Images>,
+    mut damage_events: EventWriter<DamageDealt>,
+) {
+    for (t_entity, tower, frost, transform, mut timer, mut stats) in tower_query.iter_mut() {
+        if (!tower.activated) {
+            continue;
+        }
+
+        timer.add_time(time.delta_seconds());
+        if (timer.get_time() < tower.get_rate_of_fire()) {
+            continue;
+        }
+
+        let mut hit = false;
+        for (entity, _) in grid.query_radius(transform.translation.truncate(), tower.get_range()) {
+            let Ok((e_transform, mut slow, mut health)) = enemy_query.get_mut(entity) else {
+                continue;
+            };
+            if (Vec3::distance(transform.translation, e_transform.translation) > tower.get_range()) {
+                continue;
+            }
+
+            slow.apply(frost.slow, frost.duration);
+            slow.freeze(frost.freeze);
+            if (tower.get_damage() > 0) {
+                veterancy::deal_damage(&mut health, tower.get_damage(), Some(t_entity), &mut damage_events);
+            }
+            hit = true;
+        }
+
+        if (!hit) {
+            continue;
+        }
+        stats.record_shots(1);
+        stats.record_hit();
+
+        spawn_pulse(&mut commands, &images, transform.translation, tower.get_range());
+        timer.reset();
+    }
+}
+
+/// Flashes a ring of frost with the given radius that quickly fades out.
+pub fn spawn_pulse(commands: &mut Commands, images: &Images, position: Vec3, radius: f32) {
+    let mut pulse = Transform::from_translation(Vec3::new(position.x, position.y, -0.4));
+    pulse.scale = Vec3::new(radius * 2.0 / 32.0, radius * 2.0 / 32.0, 1.0);
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.6, 0.85, 1.0, 0.6),
+                ..default()
+            },
+            transform: pulse,
+            texture: images.get("range_view"),
+            visibility: Visibility::Visible,
+            ..default()
+        },
+        FrostPulse,
+        GameTimer::new(0.0)
+    ));
+}
+
+fn update_frost_pulses(
+    mut commands: Commands,
+    mut pulse_query: Query<(Entity, &mut Sprite, &mut GameTimer), With<FrostPulse>>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut timer) in pulse_query.iter_mut() {
+        timer.add_time(time.delta_seconds());
+        sprite.color.set_a(0.6 * (1.0 - timer.get_time() / PULSE_LIFETIME));
+
+        if (timer.get_time() >= PULSE_LIFETIME) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
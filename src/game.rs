@@ -3,8 +3,10 @@ use bevy::window::PrimaryWindow;
 use bevy_egui::egui::load::SizedTexture;
 use bevy_egui::egui::{Button, Pos2};
 use bevy_egui::{egui, EguiContext, EguiUserTextures};
+use crate::random::GameRng;
 use crate::resources::Images;
-use crate::tower::{DirectionalTower, Tower};
+use crate::definitions::{spawn_tower, TowerDefinitions};
+use crate::tower::Tower;
 use crate::level::{EnemyType, ENEMY_TYPES};
 
 const TOWER_BUTTON_SIZE: [f32; 2] = [158.0, 40.0];
@@ -12,10 +14,13 @@ const TOWER_BUTTON_SIZE: [f32; 2] = [158.0, 40.0];
 pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<RoundCompleted>();
+        app.insert_resource(GameRng::from_env());
         app.add_systems(Startup, game_init);
         app.add_systems(Update, place_tower_range_view);
         app.add_systems(Update, game_ui);
         app.add_systems(Update, end_round);
+        app.add_systems(Update, check_game_over);
     }
 }
 
@@ -55,13 +60,15 @@ pub struct PlayerBase;
 pub struct Health {
     health: i32,
     max_health: i32,
+    shield: i32,
 }
 
 impl Health {
     pub fn new(max_hp: i32) -> Health {
         return Health {
             health: max_hp,
-            max_health: max_hp
+            max_health: max_hp,
+            shield: 0,
         };
     }
 
@@ -69,26 +76,49 @@ impl Health {
         return self.health;
     }
 
+    pub fn get_max_health(&self) -> i32 {
+        return self.max_health;
+    }
+
+    pub fn get_shield(&self) -> i32 {
+        return self.shield;
+    }
+
     pub fn add(&mut self, amount: i32) {
+        self.health = i32::min(self.health + amount, self.max_health);
+    }
+
+    /// Raises the maximum health and heals by the same amount.
+    pub fn raise_max(&mut self, amount: i32) {
+        self.max_health += amount;
         self.health += amount;
     }
 
+    /// Shields from several sources don't add up, the strongest one is kept.
+    pub fn add_shield(&mut self, amount: i32) {
+        self.shield = i32::max(self.shield, amount);
+    }
+
     pub fn lose(&mut self, amount: i32) {
-        self.health -= amount;
+        let absorbed = i32::min(self.shield, amount);
+        self.shield -= absorbed;
+        self.health -= amount - absorbed;
     }
 }
 
 #[derive(Resource)]
 pub struct PlayerStats {
     coins: i32,
-    pub is_placing: bool
+    pub is_placing: bool,
+    game_over: bool,
 }
 
 impl PlayerStats {
     pub fn new(amount: i32) -> PlayerStats {
         return PlayerStats {
             coins: amount,
-            is_placing: false
+            is_placing: false,
+            game_over: false,
         };
     }
 
@@ -108,6 +138,10 @@ impl PlayerStats {
         self.coins -= amount;
     }
 
+    pub fn is_game_over(&self) -> bool {
+        return self.game_over;
+    }
+
 }
 
 #[derive(Resource)]
@@ -117,6 +151,7 @@ pub struct RoundInfo {
     max_enemies: i32,
     pub enemies_spawned: i32,
     pub enemies_killed: i32,
+    pub enemies_split: i32,
     pub total_enemies: i32,
     auto_start_round: bool,
     spawn_rate: f32,
@@ -131,6 +166,7 @@ impl RoundInfo {
             max_enemies: 0,
             enemies_spawned: 0,
             enemies_killed: 0,
+            enemies_split: 0,
             total_enemies: 3,
             auto_start_round: false,
             spawn_rate: 3.0,
@@ -145,18 +181,23 @@ impl RoundInfo {
         self.max_enemies = self.total_enemies;
         self.enemies_spawned = 0;
         self.enemies_killed = 0;
+        self.enemies_split = 0;
         
         if (self.spawn_rate > 0.4) {
             self.spawn_rate -= 0.25;
         }
 
-        if (self.round % 4 == 0) {
-            let new_enemy_index = (self.round / 4) as usize;
+        let new_enemy_index = (self.round / 4) as usize;
+        if (self.round % 4 == 0 && new_enemy_index < ENEMY_TYPES.len()) {
             self.enemy_types.push(ENEMY_TYPES[new_enemy_index]);
             println!("new enemy");
         }
     }
 
+    pub fn get_round(&self) -> i32 {
+        return self.round;
+    }
+
     pub fn round_completed(&self) -> bool {
         return self.round_completed;
     }
@@ -206,7 +247,7 @@ fn game_init(
     commands.spawn((
         SpriteBundle {
             transform: Transform::from_xyz(64.0, 64.0, -0.5),
-            texture: images.range_view.clone(),
+            texture: images.get("range_view"),
             visibility: Visibility::Hidden,
             ..default()
         },
@@ -244,6 +285,8 @@ fn game_ui(
     mut tower_query: Query<&mut Tower>,
     mut egui_user_textures: ResMut<EguiUserTextures>,
     images: Res<Images>,
+    definitions: Res<TowerDefinitions>,
+    rng: Res<GameRng>,
     base_query: Query<&Health, With<PlayerBase>>,
 ) {
     let (r_transform, mut r_visible) = range_view_query.get_single_mut().unwrap();
@@ -253,9 +296,9 @@ fn game_ui(
         return;
     }
 
-    let tower1_icon = SizedTexture::new(egui_user_textures.add_image(images.tower1.clone_weak()), [32.0, 32.0]);
-    let tower2_icon = SizedTexture::new(egui_user_textures.add_image(images.tower2.clone_weak()), [32.0, 32.0]);
-    let tower3_icon = SizedTexture::new(egui_user_textures.add_image(images.tower3.clone_weak()), [32.0, 32.0]);
+    let icons: Vec<SizedTexture> = definitions.towers.iter()
+        .map(|definition| SizedTexture::new(egui_user_textures.add_image(images.get(&definition.sprite)), [32.0, 32.0]))
+        .collect();
 
     let Ok(mut ctx) = contexts.get_single_mut() else {
         return;
@@ -266,97 +309,65 @@ fn game_ui(
         ui.label(format!("Round: {}", round_info.round));
         ui.label(format!("Coins: {}", player_stats.coins));
 
-        if (round_info.round_completed()) {
+        if (round_info.round_completed() && !player_stats.is_game_over()) {
             if (ui.button("New Round").clicked()) {
                 round_info.new_round();
             }
         }
         
         ui.checkbox(&mut round_info.auto_start_round, "Auto Start New Round");
+        ui.label(format!("Seed: {}", rng.get_seed()));
 
-        if (ui.add_sized(TOWER_BUTTON_SIZE, Button::image_and_text(tower1_icon, "Tower 1 | 50 Coins")).clicked() && player_stats.get_coins() >= 50) {
-            for mut tower in tower_query.iter_mut() {
-                tower.set_selected(false);
-            }
-
-            player_stats.is_placing = true;
-
-            let mut t = Transform::from_translation(Vec3::new(-16.0, -16.0, 3.0));
-            t.scale = Vec3::new(2.0, 2.0, 2.0);
-
-            commands.spawn((SpriteBundle {
-                transform: t,
-                texture: images.tower1.clone(),
-                visibility: Visibility::Visible,
-                ..default()
-            },
-                Tower::new(150.0, 0.8, 50),
-                GameTimer::new(0.0))
-            );
-
-            (*r_visible) = Visibility::Visible;
-        }
-
-        if (ui.add_sized(TOWER_BUTTON_SIZE, Button::image_and_text(tower2_icon, "Tower 2 | 100 Coins")).clicked() && player_stats.get_coins() >= 100) {
-            for mut tower in tower_query.iter_mut() {
-                tower.set_selected(false);
-            }
-
-            player_stats.is_placing = true;
-
-            let mut t = Transform::from_translation(Vec3::new(-16.0, -16.0, 3.0));
-            t.scale = Vec3::new(2.0, 2.0, 2.0);
+        for (index, (definition, icon)) in definitions.towers.iter().zip(icons).enumerate() {
+            let label = format!("{} | {} Coins", definition.name, definition.cost);
+            if (ui.add_sized(TOWER_BUTTON_SIZE, Button::image_and_text(icon, label)).clicked() && player_stats.get_coins() >= definition.cost) {
+                for mut tower in tower_query.iter_mut() {
+                    tower.set_selected(false);
+                }
 
-            commands.spawn((SpriteBundle {
-                transform: t,
-                texture: images.tower2.clone(),
-                visibility: Visibility::Visible,
-                ..default()
-            },
-                Tower::new(120.0, 1.2, 100),
-                GameTimer::new(0.0),
-                )
-            );
+                player_stats.is_placing = true;
+                spawn_tower(&mut commands, &images, &definitions, index);
 
-            (*r_visible) = Visibility::Visible;
-        }
-
-        if (ui.add_sized(TOWER_BUTTON_SIZE, Button::image_and_text(tower3_icon, "Tower 3 | 75 Coins")).clicked() && player_stats.get_coins() >= 75) {
-            for mut tower in tower_query.iter_mut() {
-                tower.set_selected(false);
+                (*r_visible) = Visibility::Visible;
             }
-
-            player_stats.is_placing = true;
-
-            let mut t = Transform::from_translation(Vec3::new(-16.0, -16.0, 3.0));
-            t.scale = Vec3::new(2.0, 2.0, 2.0);
-
-            commands.spawn((SpriteBundle {
-                transform: t,
-                texture: images.tower3.clone(),
-                visibility: Visibility::Visible,
-                ..default()
-            },
-                Tower::new(110.0, 1.2, 75),
-                GameTimer::new(0.0),
-                DirectionalTower)
-            );
-
-            (*r_visible) = Visibility::Visible;
         }
     });
 }
 
+/// Sent once when the last enemy of a round is killed or leaks.
+#[derive(Event)]
+pub struct RoundCompleted;
+
 fn end_round(
-    mut round_info: ResMut<RoundInfo>
+    mut round_info: ResMut<RoundInfo>,
+    mut round_completed: EventWriter<RoundCompleted>,
+    player_stats: Res<PlayerStats>,
 ) {
-    if (round_info.enemies_spawned == round_info.enemies_killed 
-        && round_info.enemies_killed >= round_info.total_enemies
+    if (round_info.enemies_spawned + round_info.enemies_split == round_info.enemies_killed 
+        && round_info.enemies_spawned >= round_info.total_enemies
         && !round_info.round_completed()) {
         round_info.round_completed = true;
+        round_completed.send(RoundCompleted);
     }
 
-    if (round_info.round_completed && round_info.auto_start_round) {
+    if (round_info.round_completed && round_info.auto_start_round && !player_stats.is_game_over()) {
         round_info.new_round();
     }
-}
\ No newline at end of file
+}
+
+/// Freezes the game once the base runs out of health. The ranking of towers is
+/// shown by `stats::game_over_ui`.
+fn check_game_over(
+    base_query: Query<&Health, With<PlayerBase>>,
+    mut player_stats: ResMut<PlayerStats>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let Ok(base_health) = base_query.get_single() else {
+        return;
+    };
+
+    if (base_health.get_health() <= 0 && !player_stats.game_over) {
+        player_stats.game_over = true;
+        time.pause();
+    }
+}
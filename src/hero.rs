@@ -0,0 +1,438 @@
+use bevy::prelude::*;
+use bevy_egui::egui::Pos2;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::bullet::Bullet;
+use crate::enemy::{self, Enemy, Stealth};
+use crate::explosion;
+use crate::floating_text;
+use crate::game::{GameTimer, Health, PlayerStats};
+use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::tower;
+use crate::veterancy::DamageDealt;
+
+const HERO_SPAWN: Vec3 = Vec3::new(700.0, 450.0, 3.5);
+const HERO_SCALE: f32 = 1.5;
+const HERO_SPEED: f32 = 160.0;
+const HERO_HEALTH: i32 = 150;
+const HERO_DAMAGE: i32 = 8;
+const HERO_RANGE: f32 = 130.0;
+const HERO_ATTACK_DELAY: f32 = 0.6;
+const HERO_BULLET_SPEED: f32 = 600.0;
+const MAX_LEVEL: i32 = 10;
+/// Experience needed to go from level `n` to `n + 1` is `n` times this.
+const XP_PER_LEVEL: f32 = 150.0;
+const KILL_XP: f32 = 20.0;
+const LEVEL_DAMAGE: i32 = 3;
+const LEVEL_HEALTH: i32 = 20;
+const LEVEL_UP_COLOR: Color = Color::rgb(0.5, 0.8, 1.0);
+const RESPAWN_TIME: f32 = 8.0;
+const RESPAWN_PER_LEVEL: f32 = 1.0;
+/// Enemies this close to the hero attack it, each dealing its base damage
+/// times `ENEMY_ATTACK_RATE` every second.
+const ENEMY_REACH: f32 = 28.0;
+const ENEMY_ATTACK_RATE: f32 = 8.0;
+const CLEAVE_RADIUS: f32 = 90.0;
+const CLEAVE_MULTIPLIER: i32 = 3;
+const WAR_CRY_DURATION: f32 = 5.0;
+/// Added to the hero's attack speed during a war cry.
+const WAR_CRY_HASTE: f32 = 1.0;
+/// Share of the hero's maximum health that a war cry heals.
+const WAR_CRY_HEAL: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HeroSkill {
+    /// Damages every enemy around the hero.
+    Cleave,
+    /// Heals the hero and speeds up its attacks for a while.
+    WarCry,
+}
+
+impl HeroSkill {
+    pub const ALL: [HeroSkill; 2] = [
+        HeroSkill::Cleave,
+        HeroSkill::WarCry,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            HeroSkill::Cleave => "Cleave",
+            HeroSkill::WarCry => "War Cry",
+        };
+    }
+
+    fn key(&self) -> KeyCode {
+        return match self {
+            HeroSkill::Cleave => KeyCode::E,
+            HeroSkill::WarCry => KeyCode::R,
+        };
+    }
+
+    fn key_name(&self) -> &'static str {
+        return match self {
+            HeroSkill::Cleave => "E",
+            HeroSkill::WarCry => "R",
+        };
+    }
+
+    fn cooldown(&self) -> f32 {
+        return match self {
+            HeroSkill::Cleave => 8.0,
+            HeroSkill::WarCry => 20.0,
+        };
+    }
+}
+
+/// The player's hero. It walks to wherever the player right-clicks, shoots the
+/// closest enemy in range, and respawns at `HERO_SPAWN` after it's defeated.
+#[derive(Component)]
+pub struct Hero {
+    level: i32,
+    xp: f32,
+    damage: i32,
+    destination: Option<Vec3>,
+    cooldowns: [f32; 2],
+    war_cry: f32,
+    /// Seconds until the hero comes back, while it's defeated.
+    respawn: f32,
+    /// Damage from enemies that hasn't added up to a whole point yet.
+    pending_damage: f32,
+}
+
+impl Hero {
+    pub fn new() -> Hero {
+        return Hero {
+            level: 1,
+            xp: 0.0,
+            damage: HERO_DAMAGE,
+            destination: None,
+            cooldowns: [0.0; 2],
+            war_cry: 0.0,
+            respawn: 0.0,
+            pending_damage: 0.0,
+        };
+    }
+
+    pub fn is_alive(&self) -> bool {
+        return self.respawn <= 0.0;
+    }
+
+    pub fn get_cooldown(&self, skill: HeroSkill) -> f32 {
+        return self.cooldowns[skill as usize];
+    }
+
+    fn attack_delay(&self) -> f32 {
+        if (self.war_cry > 0.0) {
+            return HERO_ATTACK_DELAY / (1.0 + WAR_CRY_HASTE);
+        }
+
+        return HERO_ATTACK_DELAY;
+    }
+
+    fn next_level_xp(&self) -> f32 {
+        return XP_PER_LEVEL * self.level as f32;
+    }
+}
+
+/// Asks for one of the hero's skills to be used. Ignored while it's on cooldown.
+#[derive(Event)]
+pub struct UseHeroSkill {
+    pub skill: HeroSkill,
+}
+
+pub struct HeroPlugin;
+impl Plugin for HeroPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UseHeroSkill>();
+        app.add_systems(Startup, spawn_hero);
+        // Runs first so a right click that cancels placing a tower doesn't
+        // also move the hero.
+        app.add_systems(Update, hero_input.before(tower::place_tower));
+        app.add_systems(Update, move_hero.after(hero_input));
+        app.add_systems(Update, hero_attack);
+        app.add_systems(Update, use_hero_skills.after(hero_input));
+        app.add_systems(Update, enemies_attack_hero);
+        app.add_systems(Update, hero_experience);
+        app.add_systems(Update, update_hero.after(enemies_attack_hero));
+        app.add_systems(Update, hero_ui);
+    }
+}
+
+fn spawn_hero(mut commands: Commands, images: Res<Images>) {
+    let mut transform = Transform::from_translation(HERO_SPAWN);
+    transform.scale = Vec3::splat(HERO_SCALE);
+
+    commands.spawn((
+        SpriteBundle {
+            transform,
+            texture: images.get("hero"),
+            visibility: Visibility::Visible,
+            ..default()
+        },
+        Hero::new(),
+        Health::new(HERO_HEALTH),
+        GameTimer::new(0.0)
+    ));
+}
+
+fn hero_input(
+    mut hero_query: Query<&mut Hero>,
+    mut skill_events: EventWriter<UseHeroSkill>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mouse: Res<Input<MouseButton>>,
+    keyboard: Res<Input<KeyCode>>,
+    player_stats: Res<PlayerStats>,
+    mut contexts: EguiContexts,
+) {
+    for skill in HeroSkill::ALL {
+        if (keyboard.just_pressed(skill.key())) {
+            skill_events.send(UseHeroSkill { skill });
+        }
+    }
+
+    if (!mouse.just_pressed(MouseButton::Right) || player_stats.is_placing || contexts.ctx_mut().is_pointer_over_area()) {
+        return;
+    }
+
+    let Ok(mut hero) = hero_query.get_single_mut() else {
+        return;
+    };
+    let (camera, camera_transform) = camera_query.single();
+
+    if let Some(world_position) = windows.single().cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor)) {
+        if (hero.is_alive()) {
+            hero.destination = Some(world_position.extend(HERO_SPAWN.z));
+        }
+    }
+}
+
+fn move_hero(
+    mut hero_query: Query<(&mut Hero, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (mut hero, mut transform) in hero_query.iter_mut() {
+        let Some(destination) = hero.destination else {
+            continue;
+        };
+
+        let difference = destination - transform.translation;
+        let step = HERO_SPEED * time.delta_seconds();
+
+        if (difference.length() <= step) {
+            transform.translation = destination;
+            hero.destination = None;
+        } else {
+            transform.translation += difference.normalize() * step;
+            transform.rotation = Quat::from_rotation_z(f32::atan2(difference.y, difference.x));
+        }
+    }
+}
+
+fn hero_attack(
+    mut commands: Commands,
+    mut hero_query: Query<(Entity, &Hero, &mut Transform, &mut GameTimer)>,
+    enemy_query: Query<(&Health, Option<&Stealth>), With<Enemy>>,
+    grid: Res<SpatialGrid>,
+    images: Res<Images>,
+    time: Res<Time>,
+) {
+    for (entity, hero, mut transform, mut timer) in hero_query.iter_mut() {
+        if (!hero.is_alive()) {
+            continue;
+        }
+
+        timer.add_time(time.delta_seconds());
+
+        let position = transform.translation.truncate();
+        let target = grid.query_radius(position, HERO_RANGE).into_iter()
+            .filter(|(e_entity, _)| enemy_query.get(*e_entity).is_ok_and(|(health, stealth)| {
+                health.get_health() > 0 && enemy::visible_to_team(stealth)
+            }))
+            .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)));
+
+        let Some((_, target_pos)) = target else {
+            continue;
+        };
+        if (timer.get_time() < hero.attack_delay()) {
+            continue;
+        }
+
+        let dir = (target_pos - position).normalize_or_zero();
+        let angle = f32::atan2(dir.y, dir.x);
+        transform.rotation = Quat::from_rotation_z(angle);
+
+        let mut bullet = Transform::from_translation(transform.translation);
+        bullet.rotation = Quat::from_rotation_z(angle);
+
+        commands.spawn((
+            Bullet::new(hero.damage, dir.extend(0.0), HERO_BULLET_SPEED, HERO_RANGE / HERO_BULLET_SPEED + 0.1).with_source(entity),
+            SpriteBundle {
+                transform: bullet,
+                texture: images.get("bullet"),
+                visibility: Visibility::Visible,
+                ..default()
+            },
+            GameTimer::new(0.0)
+        ));
+        timer.reset();
+    }
+}
+
+fn use_hero_skills(
+    mut commands: Commands,
+    mut skill_events: EventReader<UseHeroSkill>,
+    mut hero_query: Query<(Entity, &mut Hero, &Transform, &mut Health)>,
+    images: Res<Images>,
+) {
+    for event in skill_events.read() {
+        let Ok((entity, mut hero, transform, mut health)) = hero_query.get_single_mut() else {
+            continue;
+        };
+        if (!hero.is_alive() || hero.get_cooldown(event.skill) > 0.0) {
+            continue;
+        }
+
+        match event.skill {
+            HeroSkill::Cleave => {
+                explosion::spawn_explosion(&mut commands, &images, transform.translation, CLEAVE_RADIUS, hero.damage * CLEAVE_MULTIPLIER, 0.0, Some(entity));
+            }
+            HeroSkill::WarCry => {
+                hero.war_cry = WAR_CRY_DURATION;
+                let heal = (health.get_max_health() as f32 * WAR_CRY_HEAL).round() as i32;
+                health.add(heal);
+            }
+        }
+
+        hero.cooldowns[event.skill as usize] = event.skill.cooldown();
+    }
+}
+
+fn enemies_attack_hero(
+    mut hero_query: Query<(&mut Hero, &Transform, &mut Health)>,
+    enemy_query: Query<&Enemy>,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+) {
+    for (mut hero, transform, mut health) in hero_query.iter_mut() {
+        if (!hero.is_alive()) {
+            continue;
+        }
+
+        for (entity, _) in grid.query_radius(transform.translation.truncate(), ENEMY_REACH) {
+            if let Ok(enemy) = enemy_query.get(entity) {
+                hero.pending_damage += enemy.get_damage() as f32 * ENEMY_ATTACK_RATE * time.delta_seconds();
+            }
+        }
+
+        let damage = hero.pending_damage.floor();
+        if (damage >= 1.0) {
+            hero.pending_damage -= damage;
+            health.lose(damage as i32);
+        }
+    }
+}
+
+fn hero_experience(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageDealt>,
+    mut hero_query: Query<(Entity, &mut Hero, &mut Health, &Transform)>,
+) {
+    let Ok((entity, mut hero, mut health, transform)) = hero_query.get_single_mut() else {
+        return;
+    };
+
+    for event in damage_events.read() {
+        if (event.source != entity) {
+            continue;
+        }
+
+        hero.xp += event.amount as f32;
+        if (event.killed) {
+            hero.xp += KILL_XP;
+        }
+
+        while (hero.level < MAX_LEVEL && hero.xp >= hero.next_level_xp()) {
+            hero.xp -= hero.next_level_xp();
+            hero.level += 1;
+            hero.damage += LEVEL_DAMAGE;
+            health.raise_max(LEVEL_HEALTH);
+
+            floating_text::spawn_floating_text(&mut commands, format!("Level {}!", hero.level), transform.translation, LEVEL_UP_COLOR, 20.0);
+        }
+    }
+}
+
+/// Ticks down skill cooldowns, and takes the hero off the field when it's
+/// defeated until its respawn timer runs out.
+fn update_hero(
+    mut hero_query: Query<(&mut Hero, &mut Health, &mut Transform, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (mut hero, mut health, mut transform, mut visibility) in hero_query.iter_mut() {
+        for cooldown in hero.cooldowns.iter_mut() {
+            *cooldown = f32::max(*cooldown - time.delta_seconds(), 0.0);
+        }
+        hero.war_cry = f32::max(hero.war_cry - time.delta_seconds(), 0.0);
+
+        if (hero.is_alive()) {
+            if (health.get_health() <= 0) {
+                hero.respawn = RESPAWN_TIME + RESPAWN_PER_LEVEL * hero.level as f32;
+                hero.destination = None;
+                hero.war_cry = 0.0;
+                hero.pending_damage = 0.0;
+                (*visibility) = Visibility::Hidden;
+            }
+            continue;
+        }
+
+        hero.respawn -= time.delta_seconds();
+        if (hero.is_alive()) {
+            let missing = health.get_max_health() - health.get_health();
+            health.add(missing);
+            transform.translation = HERO_SPAWN;
+            (*visibility) = Visibility::Visible;
+        }
+    }
+}
+
+fn hero_ui(
+    mut contexts: EguiContexts,
+    hero_query: Query<(&Hero, &Health)>,
+    mut skill_events: EventWriter<UseHeroSkill>,
+) {
+    let Ok((hero, health)) = hero_query.get_single() else {
+        return;
+    };
+
+    egui::Window::new("Hero").default_pos(Pos2::new(4.0, 520.0)).show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("Level: {}", hero.level));
+        if (hero.level < MAX_LEVEL) {
+            ui.label(format!("XP: {}/{}", hero.xp.floor(), hero.next_level_xp()));
+        }
+
+        if (!hero.is_alive()) {
+            ui.label(format!("Respawning in {:.0} seconds", hero.respawn.ceil()));
+            return;
+        }
+
+        ui.label(format!("Health: {}/{}", health.get_health(), health.get_max_health()));
+        ui.label(format!("Damage: {}", hero.damage));
+        ui.label("Right-click to move");
+
+        for skill in HeroSkill::ALL {
+            let cooldown = hero.get_cooldown(skill);
+            let label = if (cooldown <= 0.0) {
+                format!("{} ({})", skill.name(), skill.key_name())
+            } else {
+                format!("{} ({:.0}s)", skill.name(), cooldown.ceil())
+            };
+
+            if (ui.add_enabled(cooldown <= 0.0, egui::Button::new(label)).clicked()) {
+                skill_events.send(UseHeroSkill { skill });
+            }
+        }
+    });
+}
@@ -2,21 +2,86 @@ use bevy::prelude::*;
 use bevy::sprite::MaterialMesh2dBundle;
 use rand::Rng;
 use crate::game::{GameTimer, EnemySpawner, RoundInfo, Health};
-use crate::enemy::{self, Enemy, EnemyBundle};
+use crate::enemy::{self, Enemy, EnemySpawn};
+use crate::random::GameRng;
 use crate::resources::Images;
 
 const ENEMY_SPAWN_RATE: f32 = 3.0;
-pub type EnemyType = fn(Handle<Image>) -> EnemyBundle;
-pub const ENEMY_TYPES: [EnemyType; 4] = [
+const INTERCEPT_ITERATIONS: usize = 6;
+pub type EnemyType = fn(&mut Commands, &Images, EnemySpawn) -> Entity;
+pub const ENEMY_TYPES: [EnemyType; 8] = [
     enemy::weak_enemy,
     enemy::fast_enemy,
     enemy::medium_enemy,
     enemy::strong_enemy,
+    enemy::splitting_enemy,
+    enemy::healer_enemy,
+    enemy::shield_enemy,
+    enemy::stealth_enemy,
 ];
 
 #[derive(Component)]
 pub struct Waypoints {
     pub points: Vec<Vec3>,
+    distances: Vec<f32>,
+}
+
+impl Waypoints {
+    pub fn new(points: Vec<Vec3>) -> Waypoints {
+        let mut distances: Vec<f32> = Vec::new();
+        let mut total = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if (i > 0) {
+                total += Vec3::distance(points[i - 1usize], *point);
+            }
+            distances.push(total);
+        }
+
+        return Waypoints {
+            points,
+            distances,
+        };
+    }
+
+    pub fn total_length(&self) -> f32 {
+        return *self.distances.last().unwrap_or(&0.0);
+    }
+
+    /// Returns the position, heading and next waypoint id at `distance` along the route.
+    pub fn sample(&self, distance: f32) -> (Vec3, Vec3, usize) {
+        let distance = f32::clamp(distance, 0.0, self.total_length());
+
+        let mut i: usize = 1;
+        while (i < self.points.len() - 1usize && self.distances[i] < distance) {
+            i += 1usize;
+        }
+
+        let start = self.points[i - 1usize];
+        let end = self.points[i];
+        let length = self.distances[i] - self.distances[i - 1usize];
+        let dir = Vec3::normalize_or_zero(end - start);
+
+        if (length <= 0.0) {
+            return (end, dir, i);
+        }
+
+        let t = (distance - self.distances[i - 1usize]) / length;
+        return (start.lerp(end, t), dir, i);
+    }
+
+    /// Where a projectile fired from `origin` at `speed` meets an enemy that is
+    /// `distance` along the route and keeps moving at `enemy_speed`. Following
+    /// the route means the intercept stays correct around corners.
+    pub fn intercept(&self, origin: Vec3, distance: f32, enemy_speed: f32, speed: f32) -> Vec3 {
+        let mut position = self.sample(distance).0;
+
+        for _ in 0..INTERCEPT_ITERATIONS {
+            let time = Vec2::distance(origin.truncate(), position.truncate()) / speed;
+            position = self.sample(distance + enemy_speed * time).0;
+        }
+
+        return position;
+    }
 }
 
 #[derive(Component)]
@@ -95,7 +160,7 @@ fn create_points(
         commands.spawn((
             SpriteBundle {
                 transform: t,
-                texture: images.path.clone(),
+                texture: images.get("path"),
                 visibility: Visibility::Visible,
                 ..Default::default()
             },
@@ -114,17 +179,19 @@ fn create_points(
         });
     }
 
-    commands.spawn(
-        Waypoints {
-            points: ps,
-        }
-    );
+    commands.spawn(Waypoints::new(ps));
+}
+
+/// Which of the first `type_count` entries in `ENEMY_TYPES` to spawn next.
+fn pick_enemy_type(rng: &mut GameRng, type_count: usize) -> usize {
+    return rng.rng().gen_range(0usize..type_count);
 }
 
 fn spawn_enemies(
     mut commands: Commands,
     mut timer_query: Query<&mut GameTimer, With<EnemySpawner>>,
     mut round_info: ResMut<RoundInfo>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
     images: Res<Images>,
 ) {
@@ -137,19 +204,88 @@ fn spawn_enemies(
         timer.add_time(time.delta_seconds());
 
         if (timer.get_time() >= round_info.spawn_rate()) {
-            let enemy_images: [Handle<Image>; 4] = [
-                images.enemy_regular(),
-                images.square.clone_weak(),
-                images.square.clone_weak(),
-                images.square.clone_weak()
-            ];
-            
-            let type_count = round_info.enemy_types_count();
-            let num: usize = rand::thread_rng().gen_range(0usize..type_count);
-            commands.spawn(ENEMY_TYPES[num](enemy_images[num].clone_weak()));
+            let num = pick_enemy_type(&mut rng, round_info.enemy_types_count());
+            ENEMY_TYPES[num](&mut commands, &images, EnemySpawn::start());
             
             round_info.enemies_spawned += 1;
             timer.reset();
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Right 100, then up 50.
+    fn corner_route() -> Waypoints {
+        return Waypoints::new(vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(100.0, 0.0, 0.0), Vec3::new(100.0, 50.0, 0.0)]);
+    }
+
+    #[test]
+    fn route_length_adds_up_segments() {
+        assert_eq!(corner_route().total_length(), 150.0);
+    }
+
+    #[test]
+    fn sample_at_start() {
+        let (position, dir, next) = corner_route().sample(0.0);
+
+        assert_eq!(position, Vec3::ZERO);
+        assert_eq!(dir, Vec3::X);
+        assert_eq!(next, 1);
+    }
+
+    #[test]
+    fn sample_along_segments() {
+        let route = corner_route();
+
+        assert_eq!(route.sample(40.0), (Vec3::new(40.0, 0.0, 0.0), Vec3::X, 1));
+        assert_eq!(route.sample(100.0), (Vec3::new(100.0, 0.0, 0.0), Vec3::X, 1));
+        assert_eq!(route.sample(125.0), (Vec3::new(100.0, 25.0, 0.0), Vec3::Y, 2));
+        assert_eq!(route.sample(150.0), (Vec3::new(100.0, 50.0, 0.0), Vec3::Y, 2));
+    }
+
+    #[test]
+    fn sample_is_clamped_to_route() {
+        let route = corner_route();
+
+        assert_eq!(route.sample(-20.0).0, Vec3::ZERO);
+        assert_eq!(route.sample(1000.0), (Vec3::new(100.0, 50.0, 0.0), Vec3::Y, 2));
+    }
+
+    #[test]
+    fn intercept_of_still_enemy_is_its_position() {
+        let route = corner_route();
+
+        assert_eq!(route.intercept(Vec3::new(50.0, 80.0, 0.0), 30.0, 0.0, 400.0), route.sample(30.0).0);
+    }
+
+    #[test]
+    fn intercept_converges() {
+        let route = corner_route();
+        let origin = Vec3::new(60.0, 40.0, 0.0);
+        let (distance, enemy_speed, speed) = (90.0, 150.0, 300.0);
+
+        let position = route.intercept(origin, distance, enemy_speed, speed);
+        // The enemy reaches the intercept just as the projectile does, which
+        // here is past the corner.
+        let flight_time = Vec2::distance(origin.truncate(), position.truncate()) / speed;
+        let enemy_position = route.sample(distance + enemy_speed * flight_time).0;
+
+        assert!(position.x == 100.0 && position.y > 0.0);
+        assert!(Vec3::distance(position, enemy_position) < 0.01);
+    }
+
+    #[test]
+    fn same_seed_picks_same_enemy_types() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+
+        let picks_a: Vec<usize> = (0..50).map(|_| pick_enemy_type(&mut a, ENEMY_TYPES.len())).collect();
+        let picks_b: Vec<usize> = (0..50).map(|_| pick_enemy_type(&mut b, ENEMY_TYPES.len())).collect();
+
+        assert_eq!(picks_a, picks_b);
+        assert!(picks_a.iter().all(|num| *num < ENEMY_TYPES.len()));
+    }
+}
@@ -1,12 +1,28 @@
 #![allow(unused_parens)]
 
+mod ability;
+mod animation;
+mod beam;
+mod chain;
+mod definitions;
 mod level;
 mod enemy;
 mod game;
 mod tower;
+mod upgrade;
 mod bullet;
 mod explosion;
+mod farm;
+mod floating_text;
+mod frost;
+mod hero;
+mod random;
 mod resources;
+mod spatial;
+mod stats;
+mod support;
+mod synergy;
+mod veterancy;
 
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
@@ -15,10 +31,24 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugins(EguiPlugin)
+        .add_plugins(definitions::DefinitionsPlugin)
         .add_plugins(resources::ImagesPlugin)
+        .add_plugins(animation::AnimationPlugin)
         .add_plugins(tower::TowerPlugin)
         .add_plugins(enemy::EnemyPlugin)
         .add_plugins(bullet::BulletPlugin)
+        .add_plugins(explosion::ExplosionPlugin)
+        .add_plugins(frost::FrostPlugin)
+        .add_plugins(beam::BeamPlugin)
+        .add_plugins(chain::ChainPlugin)
+        .add_plugins(support::SupportPlugin)
+        .add_plugins(farm::FarmPlugin)
+        .add_plugins(floating_text::FloatingTextPlugin)
+        .add_plugins(veterancy::VeterancyPlugin)
+        .add_plugins(stats::StatsPlugin)
+        .add_plugins(ability::AbilityPlugin)
+        .add_plugins(synergy::SynergyPlugin)
+        .add_plugins(hero::HeroPlugin)
         .add_plugins(level::LevelPlugin)
         .add_plugins(game::GamePlugin)
         .run();
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Setting this environment variable replays a run with the same seed.
+const SEED_VARIABLE: &str = "TOWER_DEFENSE_SEED";
+
+/// Every random roll in the game goes through this, so a run is fully
+/// determined by its seed.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        return GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+    }
+
+    /// Uses the seed from `TOWER_DEFENSE_SEED` if it's set, otherwise a random one.
+    pub fn from_env() -> GameRng {
+        let seed = std::env::var(SEED_VARIABLE).ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen());
+
+        info!("Random seed: {}", seed);
+        return GameRng::new(seed);
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        return self.seed;
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        return &mut self.rng;
+    }
+}
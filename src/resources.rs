@@ -1,20 +1,43 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::definitions::TowerDefinitions;
+use crate::enemy::ENEMY_SHEETS;
+use crate::explosion::{EXPLOSION_FRAMES, EXPLOSION_SIZE};
+
+/// Sprites used outside of the tower definitions, which load their own.
+const SPRITES: [&str; 9] = [
+    "tower",
+    "hero",
+    "bullet",
+    "path",
+    "range_view",
+    "square",
+    "rank_1",
+    "rank_2",
+    "rank_3",
+];
 
 #[derive(Resource)]
 pub struct Images {
-    pub tower1: Handle<Image>,
-    pub tower2: Handle<Image>,
-    pub tower3: Handle<Image>,
-    pub bullet: Handle<Image>,
-    pub path: Handle<Image>,
-    pub range_view: Handle<Image>,
-    pub square: Handle<Image>,
-    enemy_regular: Handle<Image>
+    images: HashMap<String, Handle<Image>>,
+    atlases: HashMap<String, Handle<TextureAtlas>>,
 }
 
 impl Images {
-    pub fn enemy_regular(&self) -> Handle<Image> {
-        return self.enemy_regular.clone_weak();
+    /// Looks up a sprite by its file name under `assets/sprites`, without the extension.
+    pub fn get(&self, name: &str) -> Handle<Image> {
+        return self.images.get(name).cloned().unwrap_or_else(|| {
+            warn!("Missing sprite: {}", name);
+            return Handle::default();
+        });
+    }
+
+    pub fn atlas(&self, name: &str) -> Handle<TextureAtlas> {
+        return self.atlases.get(name).cloned().unwrap_or_else(|| {
+            warn!("Missing sprite sheet: {}", name);
+            return Handle::default();
+        });
     }
 }
 
@@ -25,15 +48,39 @@ impl Plugin for ImagesPlugin {
     }
 }
 
-fn load_assets(mut commands: Commands, assets: Res<AssetServer>) {
-    commands.insert_resource(Images {
-        tower1: assets.load("sprites/tower1.png"),
-        tower2: assets.load("sprites/tower2.png"),
-        tower3: assets.load("sprites/tower3.png"),
-        bullet: assets.load("sprites/bullet.png"),
-        path: assets.load("sprites/path.png"),
-        range_view: assets.load("sprites/range_view.png"),
-        square: assets.load("sprites/square.png"),
-        enemy_regular: assets.load("sprites/enemy_regular.png")
-    });
+fn load_assets(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    definitions: Res<TowerDefinitions>,
+) {
+    let mut images = Images {
+        images: HashMap::new(),
+        atlases: HashMap::new(),
+    };
+
+    for name in SPRITES {
+        images.images.insert(name.to_string(), assets.load(format!("sprites/{}.png", name)));
+    }
+
+    for definition in definitions.towers.iter() {
+        let path = &definition.upgrades;
+        let tiers = path.tiers.iter().chain(path.branches.iter().flat_map(|tiers| tiers.iter()));
+        let projectile = definition.projectile.iter().map(|projectile| &projectile.sprite);
+
+        for sprite in std::iter::once(&definition.sprite).chain(tiers.map(|tier| &tier.sprite)).chain(projectile) {
+            images.images.insert(sprite.clone(), assets.load(format!("sprites/{}.png", sprite)));
+        }
+    }
+
+    for sheet in ENEMY_SHEETS {
+        let texture = assets.load(format!("sprites/{}.png", sheet.path));
+        let atlas = TextureAtlas::from_grid(texture, sheet.cell_size, sheet.columns, 1, None, None);
+        images.atlases.insert(sheet.path.to_string(), texture_atlases.add(atlas));
+    }
+
+    let explosion = TextureAtlas::from_grid(assets.load("sprites/explosion.png"), EXPLOSION_SIZE, EXPLOSION_FRAMES, 1, None, None);
+    images.atlases.insert("explosion".to_string(), texture_atlases.add(explosion));
+
+    commands.insert_resource(images);
 }
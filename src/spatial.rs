@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+const DEFAULT_CELL_SIZE: f32 = 64.0;
+
+/// Buckets entities into square cells so asking for everything near a point
+/// only looks at the cells the query circle touches instead of every entity.
+#[derive(Resource)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(Entity, Vec2)>>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> SpatialGrid {
+        return SpatialGrid::new(DEFAULT_CELL_SIZE);
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        return SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+        };
+    }
+
+    fn cell(&self, point: Vec2) -> (i32, i32) {
+        return ((point.x / self.cell_size).floor() as i32, (point.y / self.cell_size).floor() as i32);
+    }
+
+    /// Empties every cell but keeps their allocations for the next rebuild.
+    pub fn clear(&mut self) {
+        for entries in self.cells.values_mut() {
+            entries.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, point: Vec2) {
+        let cell = self.cell(point);
+        self.cells.entry(cell).or_default().push((entity, point));
+    }
+
+    /// Every entity within `radius` of `point`, in no particular order.
+    pub fn query_radius(&self, point: Vec2, radius: f32) -> Vec<(Entity, Vec2)> {
+        let mut found = Vec::new();
+        let (min_x, min_y) = self.cell(point - Vec2::splat(radius));
+        let (max_x, max_y) = self.cell(point + Vec2::splat(radius));
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let Some(entries) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+
+                for (entity, position) in entries {
+                    if (position.distance_squared(point) <= radius * radius) {
+                        found.push((*entity, *position));
+                    }
+                }
+            }
+        }
+
+        return found;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+        return entities;
+    }
+
+    #[test]
+    fn query_radius_matches_linear_scan() {
+        let mut grid = SpatialGrid::new(64.0);
+        let mut points = Vec::new();
+
+        // A lattice that lands exactly on cell boundaries on both sides of the
+        // origin, plus points just off them.
+        for x in -12..=12 {
+            for y in -12..=12 {
+                points.push(Vec2::new(x as f32 * 16.0, y as f32 * 16.0));
+                points.push(Vec2::new(x as f32 * 16.0 - 0.5, y as f32 * 16.0 + 0.25));
+            }
+        }
+        for (i, point) in points.iter().enumerate() {
+            grid.insert(Entity::from_raw(i as u32), *point);
+        }
+
+        let centers = [Vec2::ZERO, Vec2::new(-64.0, -64.0), Vec2::new(64.0, -128.0), Vec2::new(-37.5, 91.0)];
+        for center in centers {
+            for radius in [0.0, 16.0, 64.0, 150.0] {
+                let expected: Vec<Entity> = points.iter().enumerate()
+                    .filter(|(_, point)| point.distance_squared(center) <= radius * radius)
+                    .map(|(i, _)| Entity::from_raw(i as u32))
+                    .collect();
+                let found = grid.query_radius(center, radius).into_iter().map(|(entity, _)| entity).collect();
+
+                assert!(radius == 0.0 || !expected.is_empty());
+                assert_eq!(sorted(found), sorted(expected), "center {:?}, radius {}", center, radius);
+            }
+        }
+    }
+
+    #[test]
+    fn clear_empties_grid() {
+        let mut grid = SpatialGrid::new(64.0);
+        grid.insert(Entity::from_raw(0), Vec2::new(-1.0, -1.0));
+        grid.clear();
+
+        assert!(grid.query_radius(Vec2::ZERO, 100.0).is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use bevy_egui::egui::{Align2, Grid};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::definitions::TowerDefinitions;
+use crate::farm::FarmTower;
+use crate::game::{PlayerStats, RoundInfo};
+use crate::support::SupportTower;
+use crate::tower::{DetectionTower, Tower};
+use crate::veterancy::DamageDealt;
+
+/// Sent the first time a projectile hits something, for the firing tower's hit rate.
+#[derive(Event)]
+pub struct ShotLanded {
+    pub tower: Entity,
+}
+
+/// How a tower has performed over the whole game.
+#[derive(Component, Default)]
+pub struct CombatStats {
+    damage: i32,
+    kills: i32,
+    shots: i32,
+    hits: i32,
+    /// Seconds the tower has been placed while a round was running.
+    combat_time: f32,
+}
+
+impl CombatStats {
+    pub fn record_shots(&mut self, count: i32) {
+        self.shots += count;
+    }
+
+    pub fn record_hit(&mut self) {
+        self.hits += 1;
+    }
+
+    pub fn get_damage(&self) -> i32 {
+        return self.damage;
+    }
+
+    pub fn get_kills(&self) -> i32 {
+        return self.kills;
+    }
+
+    pub fn get_shots(&self) -> i32 {
+        return self.shots;
+    }
+
+    /// Share of shots that hit something, or `None` for towers that haven't
+    /// fired any, like beams.
+    pub fn hit_rate(&self) -> Option<f32> {
+        if (self.shots == 0) {
+            return None;
+        }
+
+        return Some(self.hits as f32 / self.shots as f32);
+    }
+
+    /// Average damage per second of combat since the tower was placed.
+    pub fn dps(&self) -> f32 {
+        if (self.combat_time <= 0.0) {
+            return 0.0;
+        }
+
+        return self.damage as f32 / self.combat_time;
+    }
+}
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShotLanded>();
+        app.add_systems(Update, record_combat);
+        app.add_systems(Update, track_combat_time);
+        app.add_systems(Update, game_over_ui);
+    }
+}
+
+fn record_combat(
+    mut damage_events: EventReader<DamageDealt>,
+    mut shot_events: EventReader<ShotLanded>,
+    mut stats_query: Query<&mut CombatStats>,
+) {
+    for event in damage_events.read() {
+        if let Ok(mut stats) = stats_query.get_mut(event.source) {
+            stats.damage += event.amount;
+            if (event.killed) {
+                stats.kills += 1;
+            }
+        }
+    }
+
+    for event in shot_events.read() {
+        if let Ok(mut stats) = stats_query.get_mut(event.tower) {
+            stats.record_hit();
+        }
+    }
+}
+
+fn track_combat_time(
+    mut tower_query: Query<(&Tower, &mut CombatStats)>,
+    round_info: Res<RoundInfo>,
+    time: Res<Time>,
+) {
+    if (round_info.round_completed()) {
+        return;
+    }
+
+    for (tower, mut stats) in tower_query.iter_mut() {
+        if (tower.activated) {
+            stats.combat_time += time.delta_seconds();
+        }
+    }
+}
+
+pub fn combat_stats_ui(ui: &mut egui::Ui, stats: &CombatStats) {
+    ui.label(format!("Damage Dealt: {}", stats.get_damage()));
+    ui.label(format!("Kills: {}", stats.get_kills()));
+    if let Some(hit_rate) = stats.hit_rate() {
+        ui.label(format!("Shots: {} ({}% hit)", stats.get_shots(), (hit_rate * 100.0).round()));
+    }
+    ui.label(format!("DPS: {:.1}", stats.dps()));
+}
+
+/// Leaves out towers that never deal damage, since damage per coin says nothing about them.
+type CombatTowerFilter = (Without<FarmTower>, Without<SupportTower>, Without<DetectionTower>);
+
+/// Ranks every placed combat tower by the damage it dealt for each coin spent on it.
+fn game_over_ui(
+    mut contexts: EguiContexts,
+    player_stats: Res<PlayerStats>,
+    round_info: Res<RoundInfo>,
+    tower_query: Query<(&Tower, &CombatStats), CombatTowerFilter>,
+    definitions: Res<TowerDefinitions>,
+) {
+    if (!player_stats.is_game_over()) {
+        return;
+    }
+
+    let mut towers: Vec<(&Tower, &CombatStats, f32)> = tower_query.iter()
+        .filter(|(tower, _)| tower.activated)
+        .map(|(tower, stats)| (tower, stats, stats.get_damage() as f32 / i32::max(tower.get_invested(), 1) as f32))
+        .collect();
+    towers.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+    egui::Window::new("Game Over").anchor(Align2::CENTER_CENTER, [0.0, 0.0]).collapsible(false).show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("The base fell in round {}.", round_info.get_round()));
+        ui.separator();
+
+        Grid::new("tower_rankings").striped(true).show(ui, |ui| {
+            for header in ["#", "Tower", "Damage", "Kills", "Hit Rate", "DPS", "Spent", "Damage per Coin"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+
+            for (i, (tower, stats, value)) in towers.iter().enumerate() {
+                ui.label(format!("{}", i + 1));
+                ui.label(&definitions.get(tower.get_definition()).name);
+                ui.label(format!("{}", stats.get_damage()));
+                ui.label(format!("{}", stats.get_kills()));
+                ui.label(stats.hit_rate().map_or("-".to_string(), |rate| format!("{}%", (rate * 100.0).round())));
+                ui.label(format!("{:.1}", stats.dps()));
+                ui.label(format!("{}", tower.get_invested()));
+                ui.label(format!("{:.2}", value));
+                ui.end_row();
+            }
+        });
+    });
+}
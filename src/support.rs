@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::tower::Tower;
+
+/// Share of every buff after the strongest one that still applies.
+const SECONDARY_BUFF_SHARE: f32 = 0.5;
+/// Highest total bonus a single stat can get from stacked support towers.
+const MAX_BUFF: f32 = 0.6;
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+pub enum BuffStat {
+    Range,
+    RateOfFire,
+    Damage,
+}
+
+impl BuffStat {
+    pub const ALL: [BuffStat; 3] = [
+        BuffStat::Range,
+        BuffStat::RateOfFire,
+        BuffStat::Damage,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            BuffStat::Range => "Range",
+            BuffStat::RateOfFire => "Fire Rate",
+            BuffStat::Damage => "Damage",
+        };
+    }
+}
+
+/// Bonuses a tower currently gets from nearby support towers, as fractions of
+/// its base stats.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TowerBuffs {
+    pub range: f32,
+    pub rate_of_fire: f32,
+    pub damage: f32,
+}
+
+impl std::ops::Add for TowerBuffs {
+    type Output = TowerBuffs;
+
+    fn add(self, other: TowerBuffs) -> TowerBuffs {
+        return TowerBuffs {
+            range: self.range + other.range,
+            rate_of_fire: self.rate_of_fire + other.rate_of_fire,
+            damage: self.damage + other.damage,
+        };
+    }
+}
+
+/// Doesn't shoot, boosts one stat of every other tower within its range.
+#[derive(Component)]
+pub struct SupportTower {
+    pub stat: BuffStat,
+    pub amount: f32,
+}
+
+impl SupportTower {
+    pub fn new(stat: BuffStat, amount: f32) -> SupportTower {
+        return SupportTower {
+            stat,
+            amount,
+        };
+    }
+}
+
+pub struct SupportPlugin;
+impl Plugin for SupportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_support_buffs);
+    }
+}
+
+/// The strongest buff applies in full and every other one adds half of its
+/// value, capped at `MAX_BUFF`.
+fn stack_buffs(mut amounts: Vec<f32>) -> f32 {
+    amounts.sort_by(|a, b| b.total_cmp(a));
+
+    let mut total = 0.0;
+    for (i, amount) in amounts.iter().enumerate() {
+        total += if (i == 0) { *amount } else { *amount * SECONDARY_BUFF_SHARE };
+    }
+
+    return f32::min(total, MAX_BUFF);
+}
+
+fn apply_support_buffs(
+    support_query: Query<(&Tower, &SupportTower, &Transform)>,
+    mut tower_query: Query<(&mut Tower, &Transform), Without<SupportTower>>,
+) {
+    for (mut tower, transform) in tower_query.iter_mut() {
+        let mut range = Vec::new();
+        let mut rate_of_fire = Vec::new();
+        let mut damage = Vec::new();
+
+        for (s_tower, support, s_transform) in support_query.iter() {
+            if (!s_tower.activated || Vec3::distance(s_transform.translation, transform.translation) > s_tower.get_range()) {
+                continue;
+            }
+
+            match support.stat {
+                BuffStat::Range => range.push(support.amount),
+                BuffStat::RateOfFire => rate_of_fire.push(support.amount),
+                BuffStat::Damage => damage.push(support.amount),
+            }
+        }
+
+        let buffs = TowerBuffs {
+            range: stack_buffs(range),
+            rate_of_fire: stack_buffs(rate_of_fire),
+            damage: stack_buffs(damage),
+        };
+
+        if (tower.get_buffs() != buffs) {
+            tower.set_buffs(buffs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_buffs_add_nothing() {
+        assert_eq!(stack_buffs(Vec::new()), 0.0);
+    }
+
+    #[test]
+    fn single_buff_applies_in_full() {
+        assert_eq!(stack_buffs(vec![0.2]), 0.2);
+    }
+
+    #[test]
+    fn strongest_buff_applies_in_full_and_others_at_half() {
+        let total = stack_buffs(vec![0.1, 0.2, 0.1]);
+
+        assert!((total - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stacked_buffs_are_capped() {
+        assert_eq!(stack_buffs(vec![0.3, 0.3, 0.3, 0.3]), MAX_BUFF);
+        assert_eq!(stack_buffs(vec![0.9]), MAX_BUFF);
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+use crate::definitions::{self, TowerDefinitions};
+use crate::support::TowerBuffs;
+use crate::tower::Tower;
+
+const SYNERGIES_PATH: &str = "assets/synergies.ron";
+const LINK_COLOR: Color = Color::rgba(1.0, 0.85, 0.3, 0.7);
+
+#[derive(Deserialize)]
+struct SynergyFile {
+    adjacency: f32,
+    synergies: Vec<SynergyDefinition>,
+}
+
+/// `tower` gets `bonus` while a `partner` is adjacent, both given by tower name.
+#[derive(Deserialize)]
+struct SynergyDefinition {
+    name: String,
+    tower: String,
+    partner: String,
+    bonus: TowerBuffs,
+}
+
+struct SynergyRule {
+    name: String,
+    tower: usize,
+    partner: usize,
+    bonus: TowerBuffs,
+}
+
+/// Synergy rules loaded from `assets/synergies.ron`, along with the ones that
+/// are currently active between placed towers.
+#[derive(Resource)]
+pub struct Synergies {
+    /// Greatest distance between two tower centers for them to count as adjacent.
+    adjacency: f32,
+    rules: Vec<SynergyRule>,
+    links: Vec<(Entity, Entity)>,
+    active: HashMap<Entity, Vec<usize>>,
+}
+
+impl Synergies {
+    pub fn load(definitions: &TowerDefinitions) -> Synergies {
+        let file: SynergyFile = definitions::load_ron(SYNERGIES_PATH);
+
+        let find = |name: &str| definitions.find(name)
+            .unwrap_or_else(|| panic!("Unknown tower in {}: {}", SYNERGIES_PATH, name));
+
+        let rules = file.synergies.into_iter()
+            .map(|synergy| SynergyRule {
+                tower: find(&synergy.tower),
+                partner: find(&synergy.partner),
+                name: synergy.name,
+                bonus: synergy.bonus,
+            })
+            .collect();
+
+        return Synergies {
+            adjacency: file.adjacency,
+            rules,
+            links: Vec::new(),
+            active: HashMap::new(),
+        };
+    }
+
+    /// Names of the synergies the tower currently benefits from.
+    pub fn active_names(&self, tower: Entity) -> Vec<&str> {
+        return self.active.get(&tower).map_or(Vec::new(), |rules| {
+            rules.iter().map(|rule| self.rules[*rule].name.as_str()).collect()
+        });
+    }
+}
+
+/// Sent when a tower is placed, picked up or sold, so synergies get recomputed.
+#[derive(Event)]
+pub struct TowersChanged;
+
+pub struct SynergyPlugin;
+impl Plugin for SynergyPlugin {
+    fn build(&self, app: &mut App) {
+        let synergies = Synergies::load(app.world.resource::<TowerDefinitions>());
+        app.insert_resource(synergies);
+        app.add_event::<TowersChanged>();
+        // Sold towers are despawned through commands, which have been applied
+        // by the time PostUpdate runs.
+        app.add_systems(PostUpdate, apply_synergies);
+        app.add_systems(Update, draw_synergy_links);
+    }
+}
+
+fn apply_synergies(
+    mut changed_events: EventReader<TowersChanged>,
+    mut synergies: ResMut<Synergies>,
+    mut tower_query: Query<(Entity, &mut Tower, &Transform)>,
+) {
+    if (changed_events.is_empty()) {
+        return;
+    }
+    changed_events.clear();
+
+    let placed: Vec<(Entity, usize, Vec3)> = tower_query.iter()
+        .filter(|(_, tower, _)| tower.activated)
+        .map(|(entity, tower, transform)| (entity, tower.get_definition(), transform.translation))
+        .collect();
+
+    let synergies = synergies.as_mut();
+    synergies.links.clear();
+    synergies.active.clear();
+
+    for (entity, mut tower, transform) in tower_query.iter_mut() {
+        let mut bonus = TowerBuffs::default();
+
+        if (tower.activated) {
+            for (i, rule) in synergies.rules.iter().enumerate() {
+                if (rule.tower != tower.get_definition()) {
+                    continue;
+                }
+
+                let partners: Vec<Entity> = placed.iter()
+                    .filter(|(other, definition, position)| {
+                        *other != entity && *definition == rule.partner && Vec3::distance(*position, transform.translation) <= synergies.adjacency
+                    })
+                    .map(|(other, _, _)| *other)
+                    .collect();
+                if (partners.is_empty()) {
+                    continue;
+                }
+
+                bonus = bonus + rule.bonus;
+                synergies.active.entry(entity).or_default().push(i);
+
+                for partner in partners {
+                    let link = if (entity < partner) { (entity, partner) } else { (partner, entity) };
+                    if (!synergies.links.contains(&link)) {
+                        synergies.links.push(link);
+                    }
+                }
+            }
+        }
+
+        tower.set_synergy_bonus(bonus);
+    }
+}
+
+fn draw_synergy_links(
+    mut gizmos: Gizmos,
+    synergies: Res<Synergies>,
+    tower_query: Query<&Transform, With<Tower>>,
+) {
+    for (a, b) in synergies.links.iter() {
+        let (Ok(a), Ok(b)) = (tower_query.get(*a), tower_query.get(*b)) else {
+            continue;
+        };
+
+        gizmos.line_2d(a.translation.truncate(), b.translation.truncate(), LINK_COLOR);
+    }
+}
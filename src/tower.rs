@@ -1,13 +1,30 @@
 use bevy::prelude::*;
 use bevy_egui::egui::Pos2;
 use bevy_egui::{egui, EguiContexts};
-
-use crate::enemy::Enemy;
-use crate::bullet::Bullet;
-use crate::game::{GameTimer, RangeView};
-use crate::level::EnemyPath;
+use crate::ability::{TowerAbility, UseAbility};
+use rand::Rng;
+
+use crate::beam::BeamTower;
+use crate::definitions::{ProjectileDefinition, TowerDefinition, TowerDefinitions};
+use crate::chain::ChainTower;
+use crate::farm::FarmTower;
+use crate::enemy::{self, Enemy, Stealth};
+use crate::frost::FrostTower;
+use crate::bullet::{Bullet, Explosive};
+use crate::game::{GameTimer, Health, RangeView};
+use crate::level::{EnemyPath, Waypoints};
 use crate::game::PlayerStats;
+use crate::random::GameRng;
 use crate::resources::Images;
+use crate::spatial::SpatialGrid;
+use crate::stats::{self, CombatStats};
+use crate::support::{BuffStat, SupportTower, TowerBuffs};
+use crate::synergy::{Synergies, TowersChanged};
+use crate::upgrade::UpgradeTier;
+use crate::veterancy::Veterancy;
+
+const MIN_RATE_OF_FIRE: f32 = 0.1;
+const PROJECTILE_SPREAD: f32 = 0.15;
 
 #[derive(Component)]
 pub struct RegularTower;
@@ -15,52 +32,183 @@ pub struct RegularTower;
 #[derive(Component)]
 pub struct DirectionalTower;
 
+/// Fires slow rockets that explode using the tower's `Explosive` settings.
 #[derive(Component)]
 pub struct RocketTower;
 
+/// A placed tower that is being moved. Cancelling puts it back at `origin`.
+#[derive(Component)]
+pub struct Relocating {
+    origin: Vec3,
+}
+
+#[derive(Resource)]
+pub struct TowerSettings {
+    /// Share of the coins invested in a tower that selling it gives back.
+    pub sell_refund: f32,
+    pub move_cost: i32,
+}
+
+impl Default for TowerSettings {
+    fn default() -> TowerSettings {
+        return TowerSettings {
+            sell_refund: 0.7,
+            move_cost: 15,
+        };
+    }
+}
+
+/// Doesn't shoot, reveals stealth enemies inside its range to every tower.
+#[derive(Component)]
+pub struct DetectionTower;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TargetMode {
+    First,
+    Last,
+    Strongest,
+    Weakest,
+    Closest,
+}
+
+impl TargetMode {
+    pub const ALL: [TargetMode; 5] = [
+        TargetMode::First,
+        TargetMode::Last,
+        TargetMode::Strongest,
+        TargetMode::Weakest,
+        TargetMode::Closest,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            TargetMode::First => "First",
+            TargetMode::Last => "Last",
+            TargetMode::Strongest => "Strongest",
+            TargetMode::Weakest => "Weakest",
+            TargetMode::Closest => "Closest",
+        };
+    }
+}
+
+pub type EnemyQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Enemy, &'static Health, Option<&'static Stealth>), Without<Tower>>;
+
 #[derive(Component)]
 pub struct Tower {
     pub activated: bool,
+    /// Index of the tower's entry in `TowerDefinitions`.
+    definition: usize,
     range: f32,
     direction: Vec3,
     rate_of_fire: f32,
+    damage: i32,
+    projectiles: i32,
+    projectile: Option<ProjectileDefinition>,
+    accuracy: f32,
+    crit_chance: f32,
+    crit_multiplier: f32,
+    damage_variance: f32,
     cost: i32,
+    invested: i32,
     selected: bool,
-    level: i32
+    level: i32,
+    branch: Option<usize>,
+    target_mode: TargetMode,
+    target: Option<Entity>,
+    buffs: TowerBuffs,
+    /// Bonuses earned through veterancy ranks, stacked on top of `buffs`.
+    rank_bonus: TowerBuffs,
+    /// Bonuses from an active ability, like rapid fire.
+    ability_bonus: TowerBuffs,
+    /// Bonuses from synergies with neighbouring towers.
+    synergy_bonus: TowerBuffs,
+    /// Damage multiplier for the next volley, from an overcharge ability.
+    overcharge: f32,
 }
 
 impl Tower {
-    pub fn new(range_: f32, rof: f32, price: i32) -> Tower {
+    pub fn new(index: usize, definition: &TowerDefinition) -> Tower {
         return Tower {
             activated: false,
-            range: range_,
+            definition: index,
+            range: definition.range,
             direction: Vec3::ZERO,
-            rate_of_fire: rof,
-            cost: price,
+            rate_of_fire: definition.rate_of_fire,
+            damage: definition.damage,
+            projectiles: definition.projectiles,
+            projectile: definition.projectile.clone(),
+            accuracy: definition.accuracy,
+            crit_chance: definition.crit_chance,
+            crit_multiplier: definition.crit_multiplier,
+            damage_variance: definition.damage_variance,
+            cost: definition.cost,
+            invested: definition.cost,
             selected: false,
             level: 1,
+            branch: None,
+            target_mode: TargetMode::First,
+            target: None,
+            buffs: TowerBuffs::default(),
+            rank_bonus: TowerBuffs::default(),
+            ability_bonus: TowerBuffs::default(),
+            synergy_bonus: TowerBuffs::default(),
+            overcharge: 1.0,
         }
     }
 
-    pub fn closest_in_range(&self, player_pos: Vec3, points: &Vec<Vec3>, out_closest: &mut Vec3) -> bool {
-        let mut closest = Vec3::ZERO;
-        let mut min_distance = f32::INFINITY;
+    fn can_target(&self, tower_pos: Vec3, enemy_pos: Vec3, health: &Health, stealth: Option<&Stealth>) -> bool {
+        return health.get_health() > 0 && enemy::visible_to_team(stealth) && Vec3::distance(tower_pos, enemy_pos) <= self.get_range();
+    }
+
+    /// Keeps the current target while it's alive and in range, otherwise picks a
+    /// new one using the tower's targeting mode.
+    pub fn update_target(&mut self, tower_pos: Vec3, enemy_query: &EnemyQuery, grid: &SpatialGrid) -> Option<Entity> {
+        if let Some(target) = self.target {
+            if let Ok((_, transform, _, health, stealth)) = enemy_query.get(target) {
+                if (self.can_target(tower_pos, transform.translation, health, stealth)) {
+                    return self.target;
+                }
+            }
+        }
 
-        for point in points.iter() {
-            let dist = Vec3::distance(player_pos, (*point));
+        let mut best: Option<Entity> = None;
+        let mut best_score = f32::NEG_INFINITY;
 
-            if (dist > self.range) {
+        for (entity, _) in grid.query_radius(tower_pos.truncate(), self.get_range()) {
+            let Ok((_, transform, enemy, health, stealth)) = enemy_query.get(entity) else {
+                continue;
+            };
+            if (!self.can_target(tower_pos, transform.translation, health, stealth)) {
                 continue;
             }
 
-            if (dist < min_distance) {
-                min_distance = dist;
-                closest = (*point);
+            let hit_points = (health.get_health() + health.get_shield()) as f32;
+            let score = match self.target_mode {
+                TargetMode::First => enemy.distance_traveled,
+                TargetMode::Last => -enemy.distance_traveled,
+                TargetMode::Strongest => hit_points,
+                TargetMode::Weakest => -hit_points,
+                TargetMode::Closest => -Vec3::distance(tower_pos, transform.translation),
+            };
+
+            if (score > best_score) {
+                best_score = score;
+                best = Some(entity);
             }
         }
 
-        (*out_closest) = closest;
-        return min_distance != f32::INFINITY;
+        self.target = best;
+        return best;
+    }
+
+    /// Where to aim so a projectile fired now meets the enemy further along the
+    /// path. `accuracy` blends between aiming straight at the enemy (0.0) and
+    /// the exact intercept (1.0).
+    pub fn lead_target(&self, tower_pos: Vec3, enemy_pos: Vec3, enemy: &Enemy, path: &Waypoints, projectile_speed: f32) -> Vec3 {
+        let mut intercept = path.intercept(tower_pos, enemy.distance_traveled, enemy.current_speed, projectile_speed);
+        intercept.z = enemy_pos.z;
+
+        return enemy_pos.lerp(intercept, self.accuracy);
     }
 
     pub fn rotate_towards(&self, transform: &mut Transform, point: Vec3) {
@@ -77,8 +225,70 @@ impl Tower {
         self.activated = activate;
     }
 
+    fn total_bonus(&self) -> TowerBuffs {
+        return self.buffs + self.rank_bonus + self.ability_bonus + self.synergy_bonus;
+    }
+
+    /// Range including support tower buffs, veterancy, abilities and synergies.
+    /// The same goes for the other stat getters.
     pub fn get_range(&self) -> f32 {
-        return self.range;
+        return self.range * (1.0 + self.total_bonus().range);
+    }
+
+    pub fn get_rate_of_fire(&self) -> f32 {
+        return self.rate_of_fire / (1.0 + self.total_bonus().rate_of_fire);
+    }
+
+    pub fn get_damage(&self) -> i32 {
+        return (self.damage as f32 * (1.0 + self.total_bonus().damage)).round() as i32;
+    }
+
+    /// Rolls the damage of one shot within the tower's variance, and whether
+    /// it's a critical hit. `multiplier` scales it before the crit, for overcharges.
+    pub fn roll_damage(&self, rng: &mut GameRng, multiplier: f32) -> (i32, bool) {
+        let base = self.get_damage() as f32 * multiplier;
+        let spread = base * self.damage_variance;
+        let mut damage = if (spread > 0.0) { rng.rng().gen_range(base - spread..=base + spread) } else { base };
+
+        let crit = self.crit_chance > 0.0 && rng.rng().gen::<f32>() < self.crit_chance;
+        if (crit) {
+            damage *= self.crit_multiplier;
+        }
+
+        return (damage.round() as i32, crit);
+    }
+
+    pub fn get_buffs(&self) -> TowerBuffs {
+        return self.buffs;
+    }
+
+    pub fn set_buffs(&mut self, buffs: TowerBuffs) {
+        self.buffs = buffs;
+    }
+
+    pub fn set_rank_bonus(&mut self, bonus: TowerBuffs) {
+        self.rank_bonus = bonus;
+    }
+
+    pub fn set_ability_bonus(&mut self, bonus: TowerBuffs) {
+        self.ability_bonus = bonus;
+    }
+
+    pub fn set_synergy_bonus(&mut self, bonus: TowerBuffs) {
+        self.synergy_bonus = bonus;
+    }
+
+    pub fn overcharge(&mut self, multiplier: f32) {
+        self.overcharge = multiplier;
+    }
+
+    /// Damage multiplier for the volley being fired, using up any overcharge.
+    pub fn take_overcharge(&mut self) -> f32 {
+        return std::mem::replace(&mut self.overcharge, 1.0);
+    }
+
+    pub fn get_projectiles(&self) -> i32 {
+        return self.projectiles;
     }
 
     pub fn set_selected(&mut self, selected: bool) {
@@ -89,6 +299,27 @@ impl Tower {
         return self.selected;
     }
 
+    pub fn get_definition(&self) -> usize {
+        return self.definition;
+    }
+
+    /// Coins spent on the tower, including its upgrades.
+    pub fn get_invested(&self) -> i32 {
+        return self.invested;
+    }
+
+    pub fn sell_value(&self, settings: &TowerSettings) -> i32 {
+        return (self.invested as f32 * settings.sell_refund).floor() as i32;
+    }
+
+    fn overlaps(transform: &Transform, other: &Transform) -> bool {
+        let width = 16.0 * (transform.scale.x + other.scale.x);
+        let height = 16.0 * (transform.scale.y + other.scale.y);
+
+        return f32::abs(transform.translation.x - other.translation.x) < width
+            && f32::abs(transform.translation.y - other.translation.y) < height;
+    }
+
     pub fn clicked(&self, point: Vec2, transform: &Transform) -> bool {
         let width = 32.0 * transform.scale.x;
         let height = 32.0 * transform.scale.y;
@@ -107,49 +338,84 @@ impl Tower {
 
     pub fn upgrade(&mut self, range: f32, rate_of_fire: f32) {
         self.range += range;
-        
-        if (self.rate_of_fire >= 0.1) {
-            self.rate_of_fire -= rate_of_fire;
+        self.rate_of_fire = f32::max(self.rate_of_fire - rate_of_fire, MIN_RATE_OF_FIRE);
+    }
+
+    /// Upgrades that can be bought next, with the branch each one commits to.
+    pub fn next_upgrades<'a>(&self, definitions: &'a TowerDefinitions) -> Vec<(Option<usize>, &'a UpgradeTier)> {
+        let path = &definitions.get(self.definition).upgrades;
+        let bought = (self.level - 1) as usize;
+
+        if (bought < path.tiers.len()) {
+            return vec![(None, &path.tiers[bought])];
+        }
+
+        let branch_level = bought - path.tiers.len();
+        match self.branch {
+            Some(branch) => {
+                return path.branches[branch].get(branch_level).map(|tier| (Some(branch), tier)).into_iter().collect();
+            }
+            None => {
+                return path.branches.iter().enumerate().filter_map(|(i, tiers)| tiers.first().map(|tier| (Some(i), tier))).collect();
+            }
         }
     }
-}
 
-const DIRECTIONS: [Vec3; 8] = [
-    Vec3::new(1.0, 0.0, 0.0),
-    Vec3::new(0.707106781186548f32, 0.707106781186548f32, 0.0),
-    Vec3::new(0.0, 1.0, 0.0),
-    Vec3::new(-0.707106781186548f32, 0.707106781186548f32, 0.0),
-    Vec3::new(-1.0, 0.0, 0.0),
-    Vec3::new(-0.707106781186548f32, -0.707106781186548f32, 0.0),
-    Vec3::new(0.0, -1.0, 0.0),
-    Vec3::new(0.707106781186548f32, -0.707106781186548f32, 0.0)
-];
+    pub fn apply_upgrade(&mut self, tier: &UpgradeTier, branch: Option<usize>) {
+        self.upgrade(tier.range, tier.rate_of_fire);
+        self.damage += tier.damage;
+        self.projectiles = i32::max(self.projectiles + tier.projectiles, 1);
+        self.accuracy = f32::clamp(self.accuracy + tier.accuracy, 0.0, 1.0);
+        self.crit_chance = f32::clamp(self.crit_chance + tier.crit_chance, 0.0, 1.0);
+        self.crit_multiplier += tier.crit_multiplier;
+        self.damage_variance = f32::max(self.damage_variance + tier.damage_variance, 0.0);
+        if let Some(projectile) = self.projectile.as_mut() {
+            projectile.upgrade(tier);
+        }
+        self.level += 1;
+        self.invested += tier.cost;
+
+        if (branch.is_some()) {
+            self.branch = branch;
+        }
+    }
+}
 
 pub struct TowerPlugin;
 impl Plugin for TowerPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<TowerSettings>();
         app.add_systems(Update, place_tower);
         app.add_systems(Update, update_tower);
         app.add_systems(Update, update_directional_tower);
         app.add_systems(Update, upgrade_tower);
+        app.add_systems(Update, detect_stealth);
     }
 }
 
-fn place_tower(
+pub fn place_tower(
     mut commands: Commands,
     windows: Query<&Window>,
     path_query: Query<(&Transform, &EnemyPath), Without<Tower>>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
     mouse: Res<Input<MouseButton>>,
-    mut tower_query: Query<(Entity, &mut Tower, &mut Transform)>,
+    mut tower_query: Query<(Entity, &mut Tower, &mut Transform, Option<&Relocating>)>,
     mut player_stats: ResMut<PlayerStats>,
     mut range_view_query: Query<&mut Visibility, With<RangeView>>,
+    mut contexts: EguiContexts,
+    settings: Res<TowerSettings>,
+    mut changed_events: EventWriter<TowersChanged>,
 ) {
     let window = windows.single();
     let (camera, camera_transform) = camera_query.single();
     let mut range_visibility = range_view_query.get_single_mut().unwrap();
 
-    for (entity, mut tower, mut transform) in tower_query.iter_mut() {
+    let placed: Vec<(Entity, Transform)> = tower_query.iter()
+        .filter(|(_, tower, _, _)| tower.activated)
+        .map(|(entity, _, transform, _)| (entity, *transform))
+        .collect();
+
+    for (entity, mut tower, mut transform, relocating) in tower_query.iter_mut() {
         if (tower.activated) {
             continue;
         }
@@ -160,78 +426,121 @@ fn place_tower(
             transform.translation = Vec3::new(world_position.x, world_position.y, 3.0);
             
             let mut in_path = false;
-            for (path_trans, enemy_path) in path_query.iter() {
+            for (path_trans, _) in path_query.iter() {
                 in_path = EnemyPath::point_in_path(world_position, path_trans);
                 if (in_path) {
                     break;
                 }
             }
 
+            let occupied = placed.iter().any(|(other, other_t)| *other != entity && Tower::overlaps(&transform, other_t));
+
             (*range_visibility) = Visibility::Visible;
 
-            if (mouse.just_released(MouseButton::Left)) {
-                if (in_path) {
+            if (mouse.just_released(MouseButton::Left) && !contexts.ctx_mut().is_pointer_over_area()) {
+                if (in_path || occupied) {
                     return;
                 }
 
                 tower.acitvate(true);
                 player_stats.is_placing = false;
-                player_stats.lose_coins(tower.cost);
                 (*range_visibility) = Visibility::Hidden;
+                changed_events.send(TowersChanged);
+
+                if (relocating.is_some()) {
+                    player_stats.lose_coins(settings.move_cost);
+                    commands.entity(entity).remove::<Relocating>();
+                } else {
+                    player_stats.lose_coins(tower.cost);
+                }
             }
     
             if (mouse.pressed(MouseButton::Right)) {
-                commands.entity(entity).despawn();
                 player_stats.is_placing = false;
                 (*range_visibility) = Visibility::Hidden;
+
+                if let Some(relocating) = relocating {
+                    transform.translation = relocating.origin;
+                    tower.acitvate(true);
+                    commands.entity(entity).remove::<Relocating>();
+                    changed_events.send(TowersChanged);
+                } else {
+                    commands.entity(entity).despawn();
+                }
             }
         }
     }
 }
 
+/// Towers that aim at a single target, which is every kind without its own update system.
+type AimedTowerQuery<'w, 's> = Query<'w, 's, (Entity, &'static mut Tower, &'static mut Transform, &'static mut GameTimer, &'static mut CombatStats, Option<&'static Explosive>), (Without<DirectionalTower>, Without<DetectionTower>, Without<FrostTower>, Without<BeamTower>, Without<ChainTower>, Without<SupportTower>, Without<FarmTower>)>;
+
 fn update_tower(
     mut commands: Commands,
-    mut tower_query: Query<(&mut Tower, &mut Transform, &mut GameTimer), Without<DirectionalTower>>,
-    enemy_query: Query<(&Transform, &Enemy), Without<Tower>>,
+    mut tower_query: AimedTowerQuery,
+    enemy_query: EnemyQuery,
+    waypoints: Query<&Waypoints>,
+    grid: Res<SpatialGrid>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
     images: Res<Images>
 ) {
-    let mut points: Vec<Vec3> = Vec::new();
-    for (transform, enemy) in enemy_query.iter() {
-        points.push(transform.translation + (enemy.direction * 32.0));
-    }
+    let path = waypoints.single();
 
-    for (mut tower, mut transform, mut timer) in tower_query.iter_mut() {
+    for (entity, mut tower, mut transform, mut timer, mut stats, explosive) in tower_query.iter_mut() {
         if (!tower.activated) {
             continue;
         }
-        
-        let mut closest = Vec3::ZERO;
-        let has_closest: bool = tower.closest_in_range(transform.translation, &points, &mut closest);
-        //println!("{}, {}, {}", closest.x, closest.y, closest.z);
 
-        if (!has_closest) {
+        let Some(target) = tower.update_target(transform.translation, &enemy_query, &grid) else {
             continue;
-        }
+        };
+
+        let (_, e_transform, enemy, _, _) = enemy_query.get(target).unwrap();
+        let aim = match tower.projectile.as_ref() {
+            Some(projectile) => tower.lead_target(transform.translation, e_transform.translation, enemy, path, projectile.speed),
+            None => e_transform.translation,
+        };
 
-        tower.set_direction(Vec3::normalize(closest - transform.translation));
-        tower.rotate_towards(&mut transform, closest);
+        tower.set_direction(Vec3::normalize(aim - transform.translation));
+        tower.rotate_towards(&mut transform, aim);
 
         timer.add_time(time.delta_seconds());
-        if (timer.get_time() >= tower.rate_of_fire) {
-            let mut bullet = Transform::from_translation(transform.translation);
-            bullet.rotation = transform.rotation;
-
-            commands.spawn((
-                Bullet::new(10, tower.direction, 550.0, 2.0),
-                SpriteBundle {
-                    transform: bullet,
-                    texture: images.bullet.clone(),
-                    visibility: Visibility::Visible,
-                    ..default()
-                },
-                GameTimer::new(0.0)
-            ));
+        if (timer.get_time() >= tower.get_rate_of_fire()) {
+            // Towers without a projectile can't fire, so they keep their overcharge.
+            if (tower.projectile.is_none()) {
+                continue;
+            }
+            let overcharge = tower.take_overcharge();
+            let projectile = tower.projectile.as_ref().unwrap();
+            let base_angle = f32::atan2(tower.direction.y, tower.direction.x);
+            stats.record_shots(tower.projectiles);
+
+            for i in 0..tower.projectiles {
+                let offset = (i as f32 - (tower.projectiles - 1) as f32 / 2.0) * PROJECTILE_SPREAD;
+                let angle = base_angle + offset;
+                let dir = Vec3::new(f32::cos(angle), f32::sin(angle), 0.0);
+
+                let mut bullet = Transform::from_translation(transform.translation);
+                bullet.rotation = Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, angle);
+
+                let (damage, crit) = tower.roll_damage(&mut rng, overcharge);
+                let mut shot = commands.spawn((
+                    Bullet::new(damage, dir, projectile.speed, projectile.lifetime(tower.get_range())).with_crit(crit).with_source(entity),
+                    SpriteBundle {
+                        transform: bullet,
+                        texture: images.get(&projectile.sprite),
+                        visibility: Visibility::Visible,
+                        ..default()
+                    },
+                    GameTimer::new(0.0)
+                ));
+
+                projectile.insert_behaviors(&mut shot, Some(target));
+                if let Some(explosive) = explosive {
+                    shot.insert(*explosive);
+                }
+            }
             timer.reset();
         }
     }
@@ -240,61 +549,139 @@ fn update_tower(
 fn update_directional_tower(
     mut commands: Commands,
     time: Res<Time>,
-    mut tower_query: Query<(&mut Tower, &mut Transform, &mut GameTimer), With<DirectionalTower>>,
-    enemy_query: Query<(&Transform, &Enemy), Without<Tower>>,
+    mut tower_query: Query<(Entity, &mut Tower, &mut Transform, &mut GameTimer, &mut CombatStats), With<DirectionalTower>>,
+    enemy_query: EnemyQuery,
+    grid: Res<SpatialGrid>,
+    mut rng: ResMut<GameRng>,
     images: Res<Images>,
 ) {
-    let mut points: Vec<Vec3> = Vec::new();
-    for (transform, enemy) in enemy_query.iter() {
-        points.push(transform.translation + (enemy.direction * 32.0));
-    }
-
-    for (mut tower, transform, mut timer) in tower_query.iter_mut() {
+    for (entity, mut tower, transform, mut timer, mut stats) in tower_query.iter_mut() {
         if (!tower.activated) {
             continue;
         }
-        
-        let mut closest = Vec3::ZERO;
-        let has_closest: bool = tower.closest_in_range(transform.translation, &points, &mut closest);
-        //println!("{}, {}, {}", closest.x, closest.y, closest.z);
 
-        if (!has_closest) {
+        let Some(target) = tower.update_target(transform.translation, &enemy_query, &grid) else {
             continue;
-        }
+        };
 
-        tower.set_direction(Vec3::normalize(closest - transform.translation));
+        let (_, e_transform, _, _, _) = enemy_query.get(target).unwrap();
+        tower.set_direction(Vec3::normalize(e_transform.translation - transform.translation));
 
         timer.add_time(time.delta_seconds());
-        if (timer.get_time() >= tower.rate_of_fire) {
+        if (timer.get_time() >= tower.get_rate_of_fire()) {
+            if (tower.projectile.is_none()) {
+                continue;
+            }
+            let overcharge = tower.take_overcharge();
+            let projectile = tower.projectile.as_ref().unwrap();
             let mut bullet_t = Transform::from_translation(transform.translation);
+            stats.record_shots(tower.projectiles);
 
-            for dir in DIRECTIONS {
-                let angle = f32::atan2(dir.y, dir.x);
+            for i in 0..tower.projectiles {
+                let angle = std::f32::consts::TAU * i as f32 / tower.projectiles as f32;
+                let dir = Vec3::new(f32::cos(angle), f32::sin(angle), 0.0);
                 bullet_t.rotation = Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, angle);
 
-                commands.spawn((
-                    Bullet::new(7, dir, 550.0, 1.75),
+                let (damage, crit) = tower.roll_damage(&mut rng, overcharge);
+                let mut shot = commands.spawn((
+                    Bullet::new(damage, dir, projectile.speed, projectile.lifetime(tower.get_range())).with_crit(crit).with_source(entity),
                     SpriteBundle {
                         transform: bullet_t,
-                        texture: images.bullet.clone(),
+                        texture: images.get(&projectile.sprite),
                         visibility: Visibility::Visible,
                         ..default()
                     },
                     GameTimer::new(0.0)
                 ));
+                projectile.insert_behaviors(&mut shot, None);
                 timer.reset();
             }
         }
     }
 }
 
+fn detect_stealth(
+    tower_query: Query<(&Tower, &Transform), With<DetectionTower>>,
+    mut stealth_query: Query<(&mut Stealth, &Transform), Without<Tower>>,
+) {
+    for (mut stealth, s_transform) in stealth_query.iter_mut() {
+        stealth.detected = false;
+
+        for (tower, t_transform) in tower_query.iter() {
+            if (tower.activated && Vec3::distance(t_transform.translation, s_transform.translation) <= tower.get_range()) {
+                stealth.detected = true;
+                break;
+            }
+        }
+    }
+}
+
+fn target_mode_ui(ui: &mut egui::Ui, tower: &mut Tower) {
+    let mut mode = tower.target_mode;
+    egui::ComboBox::from_label("Targeting")
+        .selected_text(mode.name())
+        .show_ui(ui, |ui| {
+            for option in TargetMode::ALL {
+                ui.selectable_value(&mut mode, option, option.name());
+            }
+        });
+
+    if (mode != tower.target_mode) {
+        tower.target_mode = mode;
+        tower.target = None;
+    }
+}
+
+/// Shows a stat as its base value, followed by the buffed value if support
+/// towers change it.
+fn stat_text(base: f32, effective: f32) -> String {
+    if ((effective - base).abs() < 0.001) {
+        return format!("{}", base);
+    }
+
+    return format!("{} ({} buffed)", base, (effective * 100.0).round() / 100.0);
+}
+
+fn crit_ui(ui: &mut egui::Ui, tower: &Tower) {
+    if (tower.crit_chance > 0.0) {
+        ui.label(format!("Crit: {}% for x{}", (tower.crit_chance * 100.0).round(), tower.crit_multiplier));
+    }
+}
+
+fn veterancy_ui(ui: &mut egui::Ui, veterancy: &Veterancy) {
+    match veterancy.next_rank_xp() {
+        Some(next) => ui.label(format!("Rank: {} ({}/{} XP)", veterancy.rank_name(), veterancy.get_xp().floor(), next)),
+        None => ui.label(format!("Rank: {}", veterancy.rank_name())),
+    };
+}
+
+fn support_stat_ui(ui: &mut egui::Ui, support: &mut SupportTower) {
+    egui::ComboBox::from_label("Boosts")
+        .selected_text(support.stat.name())
+        .show_ui(ui, |ui| {
+            for option in BuffStat::ALL {
+                ui.selectable_value(&mut support.stat, option, option.name());
+            }
+        });
+}
+
+type UpgradeQuery<'w, 's> = Query<'w, 's, (Entity, &'static mut Tower, &'static Transform, &'static mut Handle<Image>, Option<&'static DetectionTower>, Option<&'static mut FrostTower>, Option<&'static BeamTower>, Option<&'static ChainTower>, Option<&'static mut SupportTower>, Option<&'static mut FarmTower>, &'static Veterancy, &'static CombatStats, Option<&'static TowerAbility>)>;
+
 fn upgrade_tower(
-    mut tower_query: Query<(&mut Tower, &Transform)>,
+    mut commands: Commands,
+    mut tower_query: UpgradeQuery,
     camera_query: Query<(&Camera, &GlobalTransform)>,
     mouse: Res<Input<MouseButton>>,
     windows: Query<&Window>,
     mut range_view_query: Query<&mut Visibility, With<RangeView>>,
     mut contexts: EguiContexts,
+    mut player_stats: ResMut<PlayerStats>,
+    images: Res<Images>,
+    settings: Res<TowerSettings>,
+    definitions: Res<TowerDefinitions>,
+    mut ability_events: EventWriter<UseAbility>,
+    mut changed_events: EventWriter<TowersChanged>,
+    synergies: Res<Synergies>,
 ) {
     let window = windows.single();
     let (camera, camera_transform) = camera_query.single();
@@ -304,11 +691,11 @@ fn upgrade_tower(
         if let Some(world_position) = window.cursor_position()
         .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor)) {
 
-            for (mut tower, transform) in tower_query.iter_mut() {
+            for (_, mut tower, _, _, _, _, _, _, _, _, _, _, _) in tower_query.iter_mut() {
                 tower.selected = false;
             }
 
-            for (mut tower, transform) in tower_query.iter_mut() {
+            for (_, mut tower, transform, _, _, _, _, _, _, _, _, _, _) in tower_query.iter_mut() {
                 if (tower.activated && !tower.selected && tower.clicked(world_position, &transform)) {
                     tower.selected = true;
                     break;
@@ -319,16 +706,186 @@ fn upgrade_tower(
         }
     }
 
-    for (tower, transform) in tower_query.iter() {
+    for (entity, mut tower, transform, mut texture, detection, mut frost, beam, chain, mut support, mut farm, veterancy, combat, ability) in tower_query.iter_mut() {
         if (tower.selected) {
             egui::Window::new("Tower").default_pos(Pos2::new(1280.0, 720.0)).show(contexts.ctx_mut(), |ui| {
-                ui.label("Level: 1");
-                ui.label(format!("Rate of Fire: {} seconds", tower.rate_of_fire));
-                if (ui.button("Upgrade Tower (20 Coins)").clicked()) {
-                    println!("Tower upgraded!");
+                ui.label(format!("Level: {}", tower.level));
+                let active_synergies = synergies.active_names(entity);
+                if (!active_synergies.is_empty()) {
+                    ui.label(format!("Synergies: {}", active_synergies.join(", ")));
+                }
+                if (detection.is_none() && support.is_none() && farm.is_none()) {
+                    veterancy_ui(ui, veterancy);
+                    stats::combat_stats_ui(ui, combat);
+                }
+                let rate_of_fire = stat_text(tower.rate_of_fire, tower.get_rate_of_fire());
+                let mut damage = stat_text(tower.damage as f32, tower.get_damage() as f32);
+                if (tower.damage_variance > 0.0) {
+                    damage = format!("{} (±{}%)", damage, (tower.damage_variance * 100.0).round());
+                }
+
+                if (farm.is_none()) {
+                    ui.label(format!("Range: {}", stat_text(tower.range, tower.get_range())));
+                }
+                if (detection.is_some()) {
+                    ui.label(format!("Detects Stealth: Yes ({} range)", tower.get_range()));
+                } else {
+                    ui.label("Detects Stealth: No");
+                }
+
+                if let Some(farm) = farm.as_ref() {
+                    ui.label(format!("Income: {} coins per round", farm.income));
+                    ui.label(format!("Earned: {} coins", farm.earned));
+                } else if let Some(support) = support.as_mut() {
+                    ui.label(format!("Buff: +{}% {}", (support.amount * 100.0).round(), support.stat.name()));
+                    support_stat_ui(ui, support);
+                } else if let Some(frost) = frost.as_ref() {
+                    ui.label(format!("Pulse Every: {} seconds", rate_of_fire));
+                    ui.label(format!("Slow: {}% for {} seconds", (frost.slow * 100.0).round(), frost.duration));
+                    if (frost.freeze > 0.0) {
+                        ui.label(format!("Freeze: {} seconds", frost.freeze));
+                    }
+                    if (tower.damage > 0) {
+                        ui.label(format!("Damage: {}", damage));
+                    }
+                } else if let Some(beam) = beam {
+                    ui.label(format!("Damage: {} per second", damage));
+                    ui.label(format!("Ramp: x{:.2} (max x{})", beam.ramp(), beam.max_ramp));
+                    target_mode_ui(ui, &mut tower);
+                } else if let Some(chain) = chain {
+                    ui.label(format!("Rate of Fire: {} seconds", rate_of_fire));
+                    ui.label(format!("Damage: {}", damage));
+                    ui.label(format!("Jumps: {} ({} range)", tower.projectiles, chain.jump_radius));
+                    ui.label(format!("Damage per Jump: x{}", chain.falloff));
+                    crit_ui(ui, &tower);
+                    target_mode_ui(ui, &mut tower);
+                } else if (detection.is_none()) {
+                    ui.label(format!("Rate of Fire: {} seconds", rate_of_fire));
+                    ui.label(format!("Damage: {}", damage));
+                    ui.label(format!("Projectiles: {}", tower.projectiles));
+                    ui.label(format!("Accuracy: {}%", (tower.accuracy * 100.0).round()));
+                    crit_ui(ui, &tower);
+                    if let Some(projectile) = tower.projectile.as_ref() {
+                        if let Some(homing) = projectile.homing {
+                            ui.label(format!("Homing: {} degrees per second", homing.turn_rate.to_degrees().round()));
+                        }
+                        if let Some(pierce) = projectile.pierce {
+                            ui.label(format!("Pierces: {} enemies", pierce.count));
+                        }
+                        if let Some(ricochet) = projectile.ricochet {
+                            ui.label(format!("Ricochets: {} ({} range)", ricochet.bounces, ricochet.radius));
+                        }
+                    }
+                    target_mode_ui(ui, &mut tower);
+                }
+
+                if let Some(ability) = ability {
+                    let label = if (ability.is_ready()) {
+                        format!("{} (Q)", ability.definition.name)
+                    } else {
+                        format!("{} ({:.0}s)", ability.definition.name, ability.get_cooldown().ceil())
+                    };
+
+                    if (ui.add_enabled(ability.is_ready(), egui::Button::new(label)).on_hover_text(ability.definition.describe()).clicked()) {
+                        ability_events.send(UseAbility { tower: entity });
+                    }
+                }
+
+                let upgrades = tower.next_upgrades(&definitions);
+                if (upgrades.is_empty()) {
+                    ui.label("Fully upgraded");
+                }
+
+                for (branch, tier) in upgrades {
+                    let can_afford = player_stats.get_coins() >= tier.cost;
+                    let button = ui.add_enabled(can_afford, egui::Button::new(format!("{} ({} Coins)", tier.name, tier.cost)))
+                        .on_hover_text(tier.describe());
+
+                    if (button.clicked()) {
+                        player_stats.lose_coins(tier.cost);
+                        tower.apply_upgrade(tier, branch);
+                        (*texture) = images.get(&tier.sprite);
+
+                        if let Some(frost) = frost.as_mut() {
+                            frost.freeze += tier.freeze;
+                        }
+                        if let Some(support) = support.as_mut() {
+                            support.amount += tier.buff;
+                        }
+                        if let Some(farm) = farm.as_mut() {
+                            farm.income += tier.income;
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                let can_move = player_stats.get_coins() >= settings.move_cost;
+                if (ui.add_enabled(can_move, egui::Button::new(format!("Move ({} Coins)", settings.move_cost))).clicked()) {
+                    tower.selected = false;
+                    tower.acitvate(false);
+                    tower.target = None;
+                    player_stats.is_placing = true;
+                    commands.entity(entity).insert(Relocating { origin: transform.translation });
+                    changed_events.send(TowersChanged);
+                }
+
+                if (ui.button(format!("Sell ({} Coins)", tower.sell_value(&settings))).clicked()) {
+                    player_stats.add_coins(tower.sell_value(&settings));
+                    commands.entity(entity).despawn();
+                    (*range_view) = Visibility::Hidden;
+                    changed_events.send(TowersChanged);
                 }
             });
             return;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_tower(damage_variance: f32, crit_chance: f32) -> Tower {
+        let definition: TowerDefinition = ron::from_str(&format!(
+            "(name: \"Test\", sprite: \"tower\", cost: 50, range: 100.0, damage: 40, damage_variance: {}, crit_chance: {}, behavior: Projectile)",
+            damage_variance, crit_chance
+        )).unwrap();
+
+        return Tower::new(0, &definition);
+    }
+
+    #[test]
+    fn same_seed_rolls_same_damage() {
+        let tower = test_tower(0.25, 0.3);
+        let mut a = GameRng::new(7);
+        let mut b = GameRng::new(7);
+
+        let rolls_a: Vec<(i32, bool)> = (0..100).map(|_| tower.roll_damage(&mut a, 1.0)).collect();
+        let rolls_b: Vec<(i32, bool)> = (0..100).map(|_| tower.roll_damage(&mut b, 1.0)).collect();
+
+        assert_eq!(rolls_a, rolls_b);
+        assert!(rolls_a.iter().any(|(_, crit)| *crit));
+    }
+
+    #[test]
+    fn rolls_stay_within_variance() {
+        let tower = test_tower(0.25, 0.0);
+        let mut rng = GameRng::new(1);
+
+        for _ in 0..1000 {
+            let (damage, _) = tower.roll_damage(&mut rng, 1.0);
+            assert!((30..=50).contains(&damage), "rolled {}", damage);
+        }
+    }
+
+    #[test]
+    fn zero_crit_chance_never_crits() {
+        let tower = test_tower(0.0, 0.0);
+        let mut rng = GameRng::new(3);
+
+        for _ in 0..1000 {
+            assert_eq!(tower.roll_damage(&mut rng, 1.0), (40, false));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use serde::Deserialize;
+
+/// Stat changes bought with one upgrade. `rate_of_fire` is subtracted from the
+/// tower's delay between shots, so positive values make it shoot faster.
+/// Stats left out of the definitions file don't change.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct UpgradeTier {
+    pub name: String,
+    pub cost: i32,
+    pub range: f32,
+    pub rate_of_fire: f32,
+    pub damage: i32,
+    pub projectiles: i32,
+    pub accuracy: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    pub damage_variance: f32,
+    /// Extra enemies the tower's projectiles pass through.
+    pub pierce: i32,
+    /// Extra times the tower's projectiles bounce to another enemy.
+    pub bounces: i32,
+    pub freeze: f32,
+    /// Added to a support tower's buff, as a fraction of the buffed stat.
+    pub buff: f32,
+    /// Extra coins a farm pays out each round.
+    pub income: i32,
+    pub sprite: String,
+}
+
+impl UpgradeTier {
+    pub fn describe(&self) -> String {
+        let mut changes: Vec<String> = Vec::new();
+
+        if (self.range != 0.0) {
+            changes.push(format!("{:+} range", self.range));
+        }
+        if (self.rate_of_fire != 0.0) {
+            changes.push(format!("{:+.2}s between shots", -self.rate_of_fire));
+        }
+        if (self.damage != 0) {
+            changes.push(format!("{:+} damage", self.damage));
+        }
+        if (self.projectiles != 0) {
+            changes.push(format!("{:+} projectiles", self.projectiles));
+        }
+        if (self.accuracy != 0.0) {
+            changes.push(format!("{:+}% accuracy", (self.accuracy * 100.0).round()));
+        }
+        if (self.crit_chance != 0.0) {
+            changes.push(format!("{:+}% crit chance", (self.crit_chance * 100.0).round()));
+        }
+        if (self.crit_multiplier != 0.0) {
+            changes.push(format!("{:+}x crit damage", self.crit_multiplier));
+        }
+        if (self.damage_variance != 0.0) {
+            changes.push(format!("{:+}% damage spread", (self.damage_variance * 100.0).round()));
+        }
+        if (self.pierce != 0) {
+            changes.push(format!("{:+} pierce", self.pierce));
+        }
+        if (self.bounces != 0) {
+            changes.push(format!("{:+} ricochets", self.bounces));
+        }
+        if (self.freeze != 0.0) {
+            changes.push(format!("freezes for {:+}s", self.freeze));
+        }
+        if (self.buff != 0.0) {
+            changes.push(format!("{:+}% buff", (self.buff * 100.0).round()));
+        }
+        if (self.income != 0) {
+            changes.push(format!("{:+} coins per round", self.income));
+        }
+
+        return changes.join(", ");
+    }
+}
+
+/// Tiers are bought in order. Once they run out the player picks one of the
+/// branches, if any, and can only keep upgrading along that branch.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct UpgradePath {
+    pub tiers: Vec<UpgradeTier>,
+    pub branches: Vec<Vec<UpgradeTier>>,
+}
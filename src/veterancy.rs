@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use crate::floating_text;
+use crate::game::Health;
+use crate::resources::Images;
+use crate::support::TowerBuffs;
+use crate::tower::Tower;
+
+const DAMAGE_XP: f32 = 1.0;
+const KILL_XP: f32 = 20.0;
+/// Experience needed for each rank after the first.
+const RANK_XP: [f32; 3] = [300.0, 1000.0, 2500.0];
+const RANK_NAMES: [&str; 4] = ["Recruit", "Veteran", "Elite", "Legendary"];
+/// Bonus damage and fire rate gained with every rank.
+const RANK_BONUS: f32 = 0.05;
+const BADGE_OFFSET: Vec3 = Vec3::new(14.0, 14.0, 1.0);
+const RANK_UP_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+
+/// Sent whenever a tower or the hero damages an enemy, so they can be credited for it.
+#[derive(Event)]
+pub struct DamageDealt {
+    pub source: Entity,
+    pub amount: i32,
+    /// Whether this hit finished the enemy off.
+    pub killed: bool,
+}
+
+/// Experience a tower has earned from the damage it dealt and the enemies it killed.
+#[derive(Component, Default)]
+pub struct Veterancy {
+    xp: f32,
+    rank: usize,
+    badge: Option<Entity>,
+}
+
+impl Veterancy {
+    pub fn rank_name(&self) -> &'static str {
+        return RANK_NAMES[self.rank];
+    }
+
+    /// Experience needed for the next rank, if there is one.
+    pub fn next_rank_xp(&self) -> Option<f32> {
+        return RANK_XP.get(self.rank).copied();
+    }
+
+    pub fn get_xp(&self) -> f32 {
+        return self.xp;
+    }
+
+    pub fn bonus(&self) -> TowerBuffs {
+        return TowerBuffs {
+            range: 0.0,
+            rate_of_fire: RANK_BONUS * self.rank as f32,
+            damage: RANK_BONUS * self.rank as f32,
+        };
+    }
+}
+
+/// Sits next to a ranked tower and follows it around.
+#[derive(Component)]
+struct RankBadge {
+    tower: Entity,
+}
+
+pub struct VeterancyPlugin;
+impl Plugin for VeterancyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageDealt>();
+        app.add_systems(Update, gain_experience);
+        app.add_systems(Update, update_rank_badges.after(gain_experience));
+    }
+}
+
+/// Deals damage to an enemy on behalf of `source`, crediting it with the damage
+/// that landed and with the kill if this hit finished the enemy off.
+pub fn deal_damage(health: &mut Health, amount: i32, source: Option<Entity>, damage_events: &mut EventWriter<DamageDealt>) {
+    if (health.get_health() <= 0) {
+        return;
+    }
+
+    let before = health.get_health() + health.get_shield();
+    health.lose(amount);
+    let after = i32::max(health.get_health(), 0) + health.get_shield();
+
+    if let Some(source) = source {
+        damage_events.send(DamageDealt {
+            source,
+            amount: before - after,
+            killed: health.get_health() <= 0,
+        });
+    }
+}
+
+fn gain_experience(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageDealt>,
+    mut tower_query: Query<(&mut Tower, &mut Veterancy, &Transform)>,
+    images: Res<Images>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut tower, mut veterancy, transform)) = tower_query.get_mut(event.source) else {
+            continue;
+        };
+
+        veterancy.xp += event.amount as f32 * DAMAGE_XP;
+        if (event.killed) {
+            veterancy.xp += KILL_XP;
+        }
+
+        let rank = veterancy.rank;
+        while (veterancy.next_rank_xp().is_some_and(|xp| veterancy.xp >= xp)) {
+            veterancy.rank += 1;
+        }
+        if (veterancy.rank == rank) {
+            continue;
+        }
+
+        tower.set_rank_bonus(veterancy.bonus());
+        floating_text::spawn_floating_text(&mut commands, format!("{}!", veterancy.rank_name()), transform.translation, RANK_UP_COLOR, 20.0);
+
+        let texture = images.get(&format!("rank_{}", veterancy.rank));
+        match veterancy.badge {
+            Some(badge) => {
+                commands.entity(badge).insert(texture);
+            }
+            None => {
+                let badge = commands.spawn((
+                    SpriteBundle {
+                        transform: Transform::from_translation(transform.translation + BADGE_OFFSET),
+                        texture,
+                        visibility: Visibility::Visible,
+                        ..default()
+                    },
+                    RankBadge { tower: event.source }
+                )).id();
+                veterancy.badge = Some(badge);
+            }
+        }
+    }
+}
+
+fn update_rank_badges(
+    mut commands: Commands,
+    mut badge_query: Query<(Entity, &RankBadge, &mut Transform)>,
+    tower_query: Query<&Transform, (With<Tower>, Without<RankBadge>)>,
+) {
+    for (entity, badge, mut transform) in badge_query.iter_mut() {
+        let Ok(t_transform) = tower_query.get(badge.tower) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        transform.translation = t_transform.translation + BADGE_OFFSET;
+    }
+}